@@ -0,0 +1,175 @@
+use std::ops::Mul;
+
+use crate::base_types::{Float, Point};
+
+///
+/// # Аффинное преобразование плоскости
+///
+/// Точка `(x, y)` переходит в `(a x + b y + tx, c x + d y + ty)`.
+/// Произведение `first * second` -- композиция: сначала
+/// применяется `second`, затем `first`, как у матриц
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+    pub a: Float,
+    pub b: Float,
+    pub c: Float,
+    pub d: Float,
+    pub tx: Float,
+    pub ty: Float,
+}
+
+impl Affine2 {
+    pub const IDENTITY: Affine2 = Affine2 {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    pub fn new(a: Float, b: Float, c: Float, d: Float, tx: Float, ty: Float) -> Affine2 {
+        Affine2 { a, b, c, d, tx, ty }
+    }
+
+    /// # Сдвиг на `dx`, `dy`
+    pub fn translation(dx: Float, dy: Float) -> Affine2 {
+        Affine2 { tx: dx, ty: dy, ..Affine2::IDENTITY }
+    }
+
+    /// # Масштабирование относительно начала координат
+    ///
+    /// Разные `sx` и `sy` дают неравномерное масштабирование
+    pub fn scaling(sx: Float, sy: Float) -> Affine2 {
+        Affine2 { a: sx, d: sy, ..Affine2::IDENTITY }
+    }
+
+    /// # Поворот вокруг начала координат
+    ///
+    /// Угол в градусах, против часовой стрелки
+    pub fn rotation(angle: Float) -> Affine2 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Affine2 { a: cos, b: -sin, c: sin, d: cos, ..Affine2::IDENTITY }
+    }
+
+    /// # Сдвиг (скос): `x += kx y`, `y += ky x`
+    pub fn shear(kx: Float, ky: Float) -> Affine2 {
+        Affine2 { b: kx, c: ky, ..Affine2::IDENTITY }
+    }
+
+    /// # То же преобразование с неподвижной точкой `pivot`
+    ///
+    /// Например, `Affine2::rotation(90.0).about(&center)` -- поворот вокруг `center`
+    pub fn about(&self, pivot: &Point) -> Affine2 {
+        Affine2::translation(pivot.x, pivot.y)
+            * *self
+            * Affine2::translation(-pivot.x, -pivot.y)
+    }
+
+    /// # Определитель линейной части
+    ///
+    /// Во столько раз преобразование меняет площади, отрицателен
+    /// для отражений
+    pub fn determinant(&self) -> Float {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// # Обратное преобразование
+    ///
+    /// `None` для вырожденного преобразования
+    pub fn inverse(&self) -> Option<Affine2> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Affine2 {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + b * self.ty),
+            ty: -(c * self.tx + d * self.ty),
+        })
+    }
+
+    /// # Образ точки
+    pub fn apply(&self, point: &Point) -> Point {
+        Point {
+            x: self.a * point.x + self.b * point.y + self.tx,
+            y: self.c * point.x + self.d * point.y + self.ty,
+        }
+    }
+
+    /// # Образ вектора: линейная часть без сдвига
+    pub fn apply_vector(&self, vector: &Point) -> Point {
+        Point {
+            x: self.a * vector.x + self.b * vector.y,
+            y: self.c * vector.x + self.d * vector.y,
+        }
+    }
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Affine2::IDENTITY
+    }
+}
+
+impl Mul for Affine2 {
+    type Output = Affine2;
+
+    fn mul(self, rhs: Affine2) -> Affine2 {
+        Affine2 {
+            a: self.a * rhs.a + self.b * rhs.c,
+            b: self.a * rhs.b + self.b * rhs.d,
+            c: self.c * rhs.a + self.d * rhs.c,
+            d: self.c * rhs.b + self.d * rhs.d,
+            tx: self.a * rhs.tx + self.b * rhs.ty + self.tx,
+            ty: self.c * rhs.tx + self.d * rhs.ty + self.ty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_applies_right_first() {
+        let shift_then_scale = Affine2::scaling(2.0, 3.0) * Affine2::translation(1.0, 1.0);
+
+        assert_eq!(Point { x: 4.0, y: 6.0 }, shift_then_scale.apply(&Point { x: 1.0, y: 1.0 }));
+    }
+
+    #[test]
+    fn rotation_about_pivot() {
+        let rotate = Affine2::rotation(90.0).about(&Point { x: 1.0, y: 1.0 });
+        let image = rotate.apply(&Point { x: 2.0, y: 1.0 });
+
+        assert_relative_eq!(1.0, image.x, epsilon = 1e-6);
+        assert_relative_eq!(2.0, image.y, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn inverse_undoes() {
+        let affine = Affine2::new(2.0, 1.0, -1.0, 3.0, 5.0, -2.0);
+        let point = Point { x: 0.5, y: -4.0 };
+
+        let back = affine.inverse().unwrap().apply(&affine.apply(&point));
+
+        assert_relative_eq!(point.x, back.x, epsilon = 1e-5);
+        assert_relative_eq!(point.y, back.y, epsilon = 1e-5);
+        assert_eq!(None, Affine2::scaling(1.0, 0.0).inverse());
+    }
+
+    #[test]
+    fn shear_keeps_area() {
+        assert_eq!(1.0, Affine2::shear(2.0, 0.0).determinant());
+        assert_eq!(-1.0, Affine2::scaling(-1.0, 1.0).determinant());
+    }
+}
@@ -1,15 +1,101 @@
 use std::fmt;
 use std::fmt::Display;
 
-
-#[derive(Debug, Clone, PartialEq, Copy)]
+use serde::{Deserialize, Serialize};
+
+///
+/// # Тип координат
+///
+/// По умолчанию `f32`, с feature `f64` -- `f64`
+///
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+#[cfg(not(feature = "f64"))]
+pub use std::f32::consts;
+#[cfg(feature = "f64")]
+pub use std::f64::consts;
+
+/// Название типа координат для сообщений об ошибках
+#[cfg(not(feature = "f64"))]
+pub const FLOAT_NAME: &str = "float32";
+#[cfg(feature = "f64")]
+pub const FLOAT_NAME: &str = "float64";
+
+
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub struct Point {
-    pub x: f32,
-    pub y: f32,
+    pub x: Float,
+    pub y: Float,
 }
 
 pub const ORIGIN: Point = Point {x:0.0, y:0.0};
 
+/// Точность сравнения координат
+pub(crate) const EPS: Float = 1e-6;
+
+/// Векторное произведение `oa` и `ob`
+pub(crate) fn cross(o: &Point, a: &Point, b: &Point) -> Float {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// # Точки `o`, `a` и `b` лежат на одной прямой
+///
+/// Допуск относительный: площадь параллелограмма сравнивается с квадратами
+/// длин его сторон, поэтому результат не зависит от масштаба
+pub(crate) fn collinear(o: &Point, a: &Point, b: &Point) -> bool {
+    let square = |p: &Point| (p.x - o.x).powi(2) + (p.y - o.y).powi(2);
+    cross(o, a, b).abs() <= EPS * (square(a) + square(b))
+}
+
+fn on_segment(p: &Point, a: &Point, b: &Point, slack: Float) -> bool {
+    p.x >= a.x.min(b.x) - slack && p.x <= a.x.max(b.x) + slack
+        && p.y >= a.y.min(b.y) - slack && p.y <= a.y.max(b.y) + slack
+}
+
+/// Проверка пересечения отрезков `ab` и `cd`, включая касание
+///
+/// Допуски относительны наибольшей координате концов отрезков
+pub(crate) fn segments_intersect(a: &Point, b: &Point, c: &Point, d: &Point) -> bool {
+    let size = [a, b, c, d].iter().fold(0.0, |size: Float, p| size.max(p.x.abs()).max(p.y.abs()));
+    let slack = EPS * size;
+    let area = slack * size;
+
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+
+    if ((d1 > area && d2 < -area) || (d1 < -area && d2 > area))
+        && ((d3 > area && d4 < -area) || (d3 < -area && d4 > area)) {
+        return true;
+    }
+
+    (d1.abs() <= area && on_segment(a, c, d, slack))
+        || (d2.abs() <= area && on_segment(b, c, d, slack))
+        || (d3.abs() <= area && on_segment(c, a, b, slack))
+        || (d4.abs() <= area && on_segment(d, a, b, slack))
+}
+
+impl Point {
+    /// # Поворот точки вокруг `pivot`
+    ///
+    /// Угол задаётся в градусах, положительное направление -- против
+    /// часовой стрелки
+    pub fn rotated(&self, pivot: &Point, angle: Float) -> Point {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let dx = self.x - pivot.x;
+        let dy = self.y - pivot.y;
+
+        Point {
+            x: pivot.x + dx * cos - dy * sin,
+            y: pivot.y + dx * sin + dy * cos,
+        }
+    }
+}
+
 
 impl std::ops::Add for Point {
     type Output = Self;
@@ -33,8 +119,8 @@ impl std::ops::Sub for Point {
     }
 }
 
-impl std::ops::MulAssign<f32> for Point {
-    fn mul_assign(&mut self, rhs: f32) {
+impl std::ops::MulAssign<Float> for Point {
+    fn mul_assign(&mut self, rhs: Float) {
         *self = Self {
             x: self.x * rhs,
             y: self.y * rhs,
@@ -49,22 +135,48 @@ impl Display for Point {
 }
 
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rect {
-    pub width: f32,
-    pub height: f32,
+    pub width: Float,
+    pub height: Float,
     pub pos: Point,
 }
 
 impl Rect {
-    fn left_bot(&self) -> Point {
+    /// # Ограничивающий прямоугольник набора точек
+    pub fn bounding(points: &[Point]) -> Rect {
+        let mut x = Float::MAX;
+        let mut x_max = Float::MIN;
+        let mut y = Float::MAX;
+        let mut y_max = Float::MIN;
+        for point in points {
+            x = x.min(point.x);
+            x_max = x_max.max(point.x);
+            y = y.min(point.y);
+            y_max = y_max.max(point.y);
+        }
+
+        let width = x_max - x;
+        let height = y_max - y;
+
+        Rect {
+            pos: Point {
+                x: x + width / 2.0,
+                y: y + height / 2.0,
+            },
+            width,
+            height,
+        }
+    }
+
+    pub(crate) fn left_bot(&self) -> Point {
         Point {
             x: self.pos.x - self.width/2.0,
             y: self.pos.y - self.height/2.0,
         }
     }
 
-    fn right_top(&self) -> Point {
+    pub(crate) fn right_top(&self) -> Point {
         Point {
             x: self.pos.x + self.width/2.0,
             y: self.pos.y + self.height/2.0,
@@ -79,3 +191,16 @@ impl Display for Rect {
     }
 }
 
+
+///
+/// # Моменты инерции площади
+///
+/// Вычисляются относительно осей, проходящих через центр масс
+/// параллельно осям координат: `ix` = ∫y²dA, `iy` = ∫x²dA, `ixy` = ∫xy dA
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Moments {
+    pub ix: Float,
+    pub iy: Float,
+    pub ixy: Float,
+}
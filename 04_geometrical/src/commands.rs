@@ -1,51 +1,93 @@
-use crate::errors::ParseScaleCommandError;
-use crate::base_types::Point;
+use crate::errors::{Diagnostic, ErrorKind};
+use crate::base_types::{Point, Rect, Float, ORIGIN};
+use crate::parse::Params;
+use crate::shape::Shape;
+use crate::union::DEFAULT_TOLERANCE;
+use crate::hull::Enclosing;
+use crate::outline::DEFAULT_EPSILON;
 
+use serde::{Deserialize, Serialize};
+
+///
+/// # Точка, относительно которой масштабируется каждая фигура
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Anchor {
+    /// Общая точка `Scale::origin`
+    #[default]
+    Origin,
+    /// Собственный центр фигуры
+    Center,
+    /// Левый нижний угол ограничивающего прямоугольника фигуры
+    Corner,
+    /// Центр ограничивающего прямоугольника всей сцены
+    Scene,
+}
+
+impl Anchor {
+    fn is_origin(&self) -> bool {
+        *self == Anchor::Origin
+    }
+}
+
+///
+/// # Масштабирование фигур относительно точки `origin`
+///
+/// `factor` -- коэффициент по оси x, `factor_y` -- по оси y, если он
+/// отличается: `SCALE x y factor [factor_y]`. Вместо точки можно указать
+/// другую опору `anchor`: `SCALE CENTER|CORNER|SCENE factor [factor_y]`,
+/// тогда `origin` не используется
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Scale {
     pub origin: Point,
-    pub factor: f32,
+    pub factor: Float,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factor_y: Option<Float>,
+    #[serde(default, skip_serializing_if = "Anchor::is_origin")]
+    pub anchor: Anchor,
 }
 
 impl std::str::FromStr for Scale {
-    type Err = ParseScaleCommandError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut params = s.split_whitespace();
-        let x = params.next().ok_or(
-            ParseScaleCommandError {
-                place: s.to_string(),
-                cause: "No parameters provided (need 3)".to_string(),
-            })?;
-        let y = params.next().ok_or(
-            ParseScaleCommandError {
-                place: s.to_string(),
-                cause: "Not enough parameters (need 3)".to_string(),
-            })?;
-        let factor = params.next().ok_or(
-            ParseScaleCommandError {
-                place: s.to_string(),
-                cause: "Not enough parameters (need 3)".to_string(),
-            })?;
-        
-        let x = x.parse::<f32>().map_err(
-            |_| ParseScaleCommandError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", x).to_string(),
-            })?;
-        let y = y.parse::<f32>().map_err(
-            |_| ParseScaleCommandError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", y).to_string(),
-            })?;
-        let origin= Point { x, y };
-
-        let factor = factor.parse::<f32>().map_err (
-            |_| ParseScaleCommandError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", factor).to_string(),
-            })?;
-
-        Ok(Scale { origin, factor })
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("SCALE", s, 3);
+
+        let anchor = match params.next_keyword(&["CENTER", "CORNER", "SCENE"]) {
+            Some("CENTER") => Anchor::Center,
+            Some("CORNER") => Anchor::Corner,
+            Some(_) => Anchor::Scene,
+            None => Anchor::Origin,
+        };
+
+        let (origin, need) = if anchor.is_origin() {
+            let x = params.next_float()?;
+            let y = params.next_float()?;
+            (Point { x, y }, 3)
+        } else {
+            (ORIGIN, 2)
+        };
+
+        let factor = params.next_float()?;
+
+        let factor_y = match params.len() {
+            n if n <= need => None,
+            n if n == need + 1 => Some(params.next_float()?),
+            _ => return Err(params.error(
+                ErrorKind::WrongParameterCount,
+                if anchor.is_origin() {
+                    "Wrong number of parameters (need 3 or 4)"
+                } else {
+                    "Wrong number of parameters (need 2 or 3)"
+                },
+            )),
+        };
+
+        params.finish()?;
+
+        Ok(Scale { origin, factor, factor_y, anchor })
     }
 }
 
@@ -54,7 +96,502 @@ impl Scale {
         Scale {
             origin: Point { x: 0.0, y: 0.0 },
             factor: 1.0,
+            factor_y: None,
+            anchor: Anchor::Origin,
+        }
+    }
+
+    /// # Коэффициенты по осям x и y
+    pub fn factors(&self) -> (Float, Float) {
+        (self.factor, self.factor_y.unwrap_or(self.factor))
+    }
+
+    /// # Опорная точка для фигуры
+    ///
+    /// `scene` -- ограничивающий прямоугольник сцены, нужен только
+    /// для `Anchor::Scene`
+    pub fn pivot(&self, shape: &dyn Shape, scene: &Rect) -> Point {
+        match self.anchor {
+            Anchor::Origin => self.origin,
+            Anchor::Center => shape.get_center(),
+            Anchor::Corner => shape.get_frame_rect().left_bot(),
+            Anchor::Scene => scene.pos,
+        }
+    }
+
+    /// # Масштабировать фигуру
+    ///
+    /// Сцена из одной фигуры: для `Anchor::Scene` опора -- центр
+    /// её ограничивающего прямоугольника
+    pub fn apply(&self, shape: &mut dyn Shape) {
+        let scene = shape.get_frame_rect();
+        let pivot = self.pivot(shape, &scene);
+        self.apply_about(shape, &pivot);
+    }
+
+    /// # Масштабировать фигуры с номерами `targets`
+    ///
+    /// Сцена для `Anchor::Scene` -- все фигуры `shapes`, а не только
+    /// масштабируемые. Несуществующие номера пропускаются
+    pub fn apply_all(&self, shapes: &mut [Box<dyn Shape>], targets: &[usize]) {
+        let corners: Vec<Point> = shapes
+            .iter()
+            .map(|shape| shape.get_frame_rect())
+            .flat_map(|frame| [frame.left_bot(), frame.right_top()])
+            .collect();
+        let scene = Rect::bounding(&corners);
+
+        for &i in targets {
+            let Some(shape) = shapes.get_mut(i) else {
+                continue;
+            };
+            let pivot = self.pivot(shape.as_ref(), &scene);
+            self.apply_about(shape.as_mut(), &pivot);
+        }
+    }
+
+    /// # Масштабировать фигуру относительно точки `pivot`
+    ///
+    /// Фигура и расстояние от её центра до `pivot` растягиваются
+    /// по каждой оси в соответствующее число раз
+    pub fn apply_about(&self, shape: &mut dyn Shape, pivot: &Point) {
+        let (sx, sy) = self.factors();
+        let center = shape.get_center();
+        if sx == sy {
+            shape.scale(sx);
+        } else {
+            shape.scale_xy(sx, sy);
+        }
+        shape.move_at(pivot);
+
+        let offset: Point = center - *pivot;
+        shape.move_by(offset.x * sx, offset.y * sy);
+    }
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///
+/// # Поворот всех фигур вокруг точки `origin` на `angle` градусов
+///
+pub struct Rotate {
+    pub origin: Point,
+    pub angle: Float,
+}
+
+impl std::str::FromStr for Rotate {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("ROTATE", s, 3);
+
+        let x = params.next_float()?;
+        let y = params.next_float()?;
+        let origin = Point { x, y };
+
+        let angle = params.next_float()?;
+
+        params.finish()?;
+
+        Ok(Rotate { origin, angle })
+    }
+}
+
+///
+/// # Сдвиг фигур на `dx`, `dy`
+///
+pub struct Move {
+    pub dx: Float,
+    pub dy: Float,
+}
+
+impl std::str::FromStr for Move {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("MOVE", s, 2);
+
+        let dx = params.next_float()?;
+        let dy = params.next_float()?;
+
+        params.finish()?;
+
+        Ok(Move { dx, dy })
+    }
+}
+
+///
+/// # Удаление фигуры с номером `index`
+///
+/// Номера фигур начинаются с нуля и соответствуют порядку описания
+///
+pub struct Delete {
+    pub index: usize,
+}
+
+impl std::str::FromStr for Delete {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("DELETE", s, 1);
+
+        let index = params.next_index()?;
+
+        params.finish()?;
+
+        Ok(Delete { index })
+    }
+}
+
+///
+/// # Запрос ограничивающего прямоугольника фигуры `index`
+///
+pub struct Frame {
+    pub index: usize,
+}
+
+impl std::str::FromStr for Frame {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("FRAME", s, 1);
+
+        let index = params.next_index()?;
+
+        params.finish()?;
+
+        Ok(Frame { index })
+    }
+}
+
+///
+/// # Выбор фигур, к которым применяются следующие команды
+///
+/// Пустой список означает выбор всех фигур
+///
+pub struct Select {
+    pub indices: Vec<usize>,
+}
+
+impl std::str::FromStr for Select {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("SELECT", s, 0);
+
+        let indices = (0..params.len())
+            .map(|_| params.next_index())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        params.finish()?;
+
+        Ok(Select { indices })
+    }
+}
+
+///
+/// # Запрос: пересекаются ли фигуры `first` и `second`
+///
+pub struct Intersects {
+    pub first: usize,
+    pub second: usize,
+}
+
+impl std::str::FromStr for Intersects {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("INTERSECTS", s, 2);
+
+        let first = params.next_index()?;
+        let second = params.next_index()?;
+
+        params.finish()?;
+
+        Ok(Intersects { first, second })
+    }
+}
+
+/// Что проверяется на принадлежность фигуре
+pub enum ContainsTarget {
+    Point(Point),
+    Shape(usize),
+}
+
+///
+/// # Запрос: содержит ли фигура `index` точку или другую фигуру
+///
+/// `CONTAINS i x y` -- проверка точки, `CONTAINS i j` -- проверка фигуры
+///
+pub struct Contains {
+    pub index: usize,
+    pub target: ContainsTarget,
+}
+
+impl std::str::FromStr for Contains {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("CONTAINS", s, 2);
+
+        let index = params.next_index()?;
+
+        let target = match params.len() {
+            0..=2 => ContainsTarget::Shape(params.next_index()?),
+            3 => {
+                let x = params.next_float()?;
+                let y = params.next_float()?;
+                ContainsTarget::Point(Point { x, y })
+            },
+            _ => return Err(params.error(
+                ErrorKind::WrongParameterCount,
+                "Wrong number of parameters (need 2 or 3)",
+            )),
+        };
+
+        params.finish()?;
+
+        Ok(Contains { index, target })
+    }
+}
+
+///
+/// # Запрос площади: суммы площадей фигур и площади их объединения
+///
+/// `tolerance` -- допустимое отклонение при замене эллипсов
+/// многоугольниками, по умолчанию `DEFAULT_TOLERANCE`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Area {
+    pub tolerance: Float,
+}
+
+impl std::str::FromStr for Area {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("AREA", s, 0);
+
+        let tolerance = match params.len() {
+            0 => DEFAULT_TOLERANCE,
+            1 => params.next_float()?,
+            _ => return Err(params.error(
+                ErrorKind::WrongParameterCount,
+                "Wrong number of parameters (need 0 or 1)",
+            )),
+        };
+        if tolerance <= 0.0 {
+            return Err(params.token_error(
+                0,
+                ErrorKind::InvalidNumber,
+                format!("Tolerance must be greater than 0, got {tolerance}"),
+            ))
+        }
+
+        params.finish()?;
+
+        Ok(Area { tolerance })
+    }
+}
+
+///
+/// # Охватывающая фигура для всех фигур сцены
+///
+/// `ENCLOSE HULL|RECT|CIRCLE [tolerance]`, `tolerance` -- как у `AREA`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Enclose {
+    pub kind: Enclosing,
+    pub tolerance: Float,
+}
+
+impl std::str::FromStr for Enclose {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("ENCLOSE", s, 1);
+
+        let kind = match params.next_keyword(&["HULL", "RECT", "CIRCLE"]) {
+            Some("HULL") => Enclosing::Hull,
+            Some("RECT") => Enclosing::Rectangle,
+            Some(_) => Enclosing::Circle,
+            None => return Err(params.token_error(
+                0,
+                ErrorKind::MissingParameter,
+                "Expected HULL, RECT or CIRCLE".to_string(),
+            )),
+        };
+
+        let tolerance = match params.len() {
+            1 => DEFAULT_TOLERANCE,
+            2 => params.next_float()?,
+            _ => return Err(params.error(
+                ErrorKind::WrongParameterCount,
+                "Wrong number of parameters (need 1 or 2)",
+            )),
+        };
+        if tolerance <= 0.0 {
+            return Err(params.token_error(
+                1,
+                ErrorKind::InvalidNumber,
+                format!("Tolerance must be greater than 0, got {tolerance}"),
+            ))
         }
+
+        params.finish()?;
+
+        Ok(Enclose { kind, tolerance })
     }
 }
 
+///
+/// # Удаление повторяющихся фигур
+///
+/// `DEDUP [epsilon]`: из фигур, совпадающих с допуском `epsilon`,
+/// остаётся первая. По умолчанию `DEFAULT_EPSILON`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dedup {
+    pub epsilon: Float,
+}
+
+impl std::str::FromStr for Dedup {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("DEDUP", s, 0);
+
+        let epsilon = match params.len() {
+            0 => DEFAULT_EPSILON,
+            1 => params.next_float()?,
+            _ => return Err(params.error(
+                ErrorKind::WrongParameterCount,
+                "Wrong number of parameters (need 0 or 1)",
+            )),
+        };
+        if epsilon <= 0.0 {
+            return Err(params.token_error(
+                0,
+                ErrorKind::InvalidNumber,
+                format!("Epsilon must be greater than 0, got {epsilon}"),
+            ))
+        }
+
+        params.finish()?;
+
+        Ok(Dedup { epsilon })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rectangle::Rectangle;
+
+    #[test]
+    fn parse_scale_optional_factor_y() {
+        let uniform: Scale = "1 1 2".parse().unwrap();
+        let stretch: Scale = "1 1 2 0.5".parse().unwrap();
+
+        assert_eq!((2.0, 2.0), uniform.factors());
+        assert_eq!((2.0, 0.5), stretch.factors());
+        assert_eq!(
+            ErrorKind::WrongParameterCount,
+            "1 1 2 0.5 7".parse::<Scale>().err().unwrap().kind,
+        );
+    }
+
+    #[test]
+    fn apply_stretches_offset() {
+        let scale: Scale = "0 0 2 1".parse().unwrap();
+        let mut rectangle: Rectangle = "1 1 3 2".parse().unwrap();
+
+        scale.apply(&mut rectangle);
+
+        assert_eq!(Point { x: 4.0, y: 1.5 }, rectangle.get_center());
+        assert_eq!(4.0, rectangle.get_area());
+    }
+
+    #[test]
+    fn parse_area_tolerance() {
+        let default: Area = "".parse().unwrap();
+        let custom: Area = "0.5".parse().unwrap();
+
+        assert_eq!(DEFAULT_TOLERANCE, default.tolerance);
+        assert_eq!(0.5, custom.tolerance);
+        assert_eq!(ErrorKind::InvalidNumber, "0".parse::<Area>().err().unwrap().kind);
+    }
+
+    #[test]
+    fn parse_scale_anchor() {
+        let center: Scale = "CENTER 2".parse().unwrap();
+        let scene: Scale = "SCENE 2 3".parse().unwrap();
+
+        assert_eq!(Anchor::Center, center.anchor);
+        assert_eq!((2.0, 3.0), scene.factors());
+        assert_eq!(Anchor::Origin, "1 1 2".parse::<Scale>().unwrap().anchor);
+        assert_eq!(
+            ErrorKind::WrongParameterCount,
+            "CORNER 2 3 4".parse::<Scale>().err().unwrap().kind,
+        );
+    }
+
+    #[test]
+    fn apply_all_about_anchors() {
+        let scene = || -> Vec<Box<dyn Shape>> {
+            vec![
+                Box::new("0 0 2 2".parse::<Rectangle>().unwrap()),
+                Box::new("4 0 6 2".parse::<Rectangle>().unwrap()),
+            ]
+        };
+        let left_bot = |shapes: &[Box<dyn Shape>], i: usize| shapes[i].get_frame_rect().left_bot();
+
+        let mut shapes = scene();
+        "CENTER 2".parse::<Scale>().unwrap().apply_all(&mut shapes, &[0, 1]);
+        assert_eq!(Point { x: 1.0, y: 1.0 }, shapes[0].get_center());
+        assert_eq!(Point { x: 5.0, y: 1.0 }, shapes[1].get_center());
+
+        let mut shapes = scene();
+        "CORNER 2".parse::<Scale>().unwrap().apply_all(&mut shapes, &[1]);
+        assert_eq!(Point { x: 4.0, y: 0.0 }, left_bot(&shapes, 1));
+        assert_eq!(16.0, shapes[1].get_area());
+
+        // Центр сцены (3, 1) считается по всем фигурам, даже немасштабируемым
+        let mut shapes = scene();
+        "SCENE 2".parse::<Scale>().unwrap().apply_all(&mut shapes, &[1]);
+        assert_eq!(Point { x: 7.0, y: 1.0 }, shapes[1].get_center());
+        assert_eq!(Point { x: 0.0, y: 0.0 }, left_bot(&shapes, 0));
+    }
+
+    #[test]
+    fn parse_enclose() {
+        let circle: Enclose = "CIRCLE 0.1".parse().unwrap();
+
+        assert_eq!(Enclose { kind: Enclosing::Circle, tolerance: 0.1 }, circle);
+        assert_eq!(Enclosing::Rectangle, "RECT".parse::<Enclose>().unwrap().kind);
+        assert_eq!(ErrorKind::MissingParameter, "BOX".parse::<Enclose>().err().unwrap().kind);
+    }
+
+    #[test]
+    fn parse_dedup_epsilon() {
+        assert_eq!(DEFAULT_EPSILON, "".parse::<Dedup>().unwrap().epsilon);
+        assert_eq!(0.01, "0.01".parse::<Dedup>().unwrap().epsilon);
+        assert_eq!(ErrorKind::InvalidNumber, "-1".parse::<Dedup>().err().unwrap().kind);
+    }
+
+    #[test]
+    fn extra_parameters_are_rejected() {
+        let kind = |err: Option<Diagnostic>| err.unwrap().kind;
+
+        assert_eq!(ErrorKind::WrongParameterCount, kind("0 0 90 1".parse::<Rotate>().err()));
+        assert_eq!(ErrorKind::WrongParameterCount, kind("1 2 3".parse::<Move>().err()));
+        assert_eq!(ErrorKind::WrongParameterCount, kind("0 1".parse::<Delete>().err()));
+        assert_eq!(ErrorKind::WrongParameterCount, kind("0 1".parse::<Frame>().err()));
+        assert_eq!(ErrorKind::WrongParameterCount, kind("0 1 2".parse::<Intersects>().err()));
+        assert!("0 0 90".parse::<Rotate>().is_ok());
+    }
+}
@@ -1,27 +1,41 @@
 
 
-use crate::errors::ParseEllipseError;
+use crate::errors::Diagnostic;
+use crate::parse::Params;
 use crate::shape::Shape;
-use crate::base_types::{Point, Rect};
+use crate::base_types::{Point, Rect, Float};
+use crate::outline::Outline;
+use crate::affine::Affine2;
+use crate::validate::{Validate, ValidationError, MAX_COORDINATE, check_numbers};
 
-use std::f32::consts::PI;
+use crate::base_types::consts::{PI, FRAC_PI_2};
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+///
+/// # Эллипс
+///
+/// `hradius` и `vradius` -- полуоси до поворота, `angle` -- поворот
+/// вокруг центра в градусах против часовой стрелки
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Ellipse {
     pub center: Point,
-    pub vradius: f32,
-    pub hradius: f32,
+    pub vradius: Float,
+    pub hradius: Float,
+    #[serde(default)]
+    pub angle: Float,
 }
 
 impl Ellipse {
-    fn new(center: Point, vradius: f32, hradius: f32) -> Ellipse {
-        Ellipse {center, vradius, hradius}
+    fn new(center: Point, vradius: Float, hradius: Float) -> Ellipse {
+        Ellipse {center, vradius, hradius, angle: 0.0}
     }
 }
 
 impl Shape for Ellipse {
-    fn get_area(&self) -> f32 {
+    fn get_area(&self) -> Float {
         PI * self.vradius * self.hradius
     }
 
@@ -30,87 +44,136 @@ impl Shape for Ellipse {
     }
 
     fn get_frame_rect(&self) -> Rect {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let (h, v) = (self.hradius, self.vradius);
+
+        let half_width = ((h * cos).powi(2) + (v * sin).powi(2)).sqrt();
+        let half_height = ((h * sin).powi(2) + (v * cos).powi(2)).sqrt();
+
         Rect {
-            width: self.hradius*2.0, 
-            height: self.vradius*2.0, 
-            pos: self.center.clone(), 
+            width: half_width*2.0, 
+            height: half_height*2.0, 
+            pos: self.center, 
         }
     }
 
     fn move_at(&mut self, target: &Point) {
-        self.center = target.clone();
+        self.center = *target;
     }
 
-    fn move_by(&mut self, x: f32, y: f32) {
+    fn move_by(&mut self, x: Float, y: Float) {
         self.center.x += x;
         self.center.y += y;
     }
 
-    fn scale(&mut self, factor: f32) {
-        self.vradius *= factor;
-        self.hradius *= factor;
+    /// # Масштабирование относительно центра
+    ///
+    /// Отрицательный коэффициент отражает эллипс относительно центра,
+    /// что его не меняет, поэтому полуоси берутся по модулю
+    fn scale(&mut self, factor: Float) {
+        self.vradius *= factor.abs();
+        self.hradius *= factor.abs();
+    }
+
+    /// # Неравномерное масштабирование
+    ///
+    /// Без поворота полуоси растягиваются независимо, повёрнутый
+    /// эллипс пересчитывается через аффинное преобразование
+    fn scale_xy(&mut self, sx: Float, sy: Float) {
+        if self.angle != 0.0 {
+            self.transform(&Affine2::scaling(sx, sy).about(&self.center));
+            return;
+        }
+        self.hradius *= sx.abs();
+        self.vradius *= sy.abs();
+    }
+
+    fn get_outline(&self) -> Outline {
+        Outline::Ellipse {
+            center: self.center,
+            hradius: self.hradius,
+            vradius: self.vradius,
+            angle: self.angle,
+        }
+    }
+
+    fn rotate(&mut self, angle: Float) {
+        self.angle = (self.angle + angle) % 360.0;
+    }
+
+    /// # Аффинное преобразование
+    ///
+    /// Образы полуосей -- сопряжённые полудиаметры нового эллипса.
+    /// Новые полуоси -- сингулярные числа матрицы `M` из этих
+    /// полудиаметров, их направления -- собственные векторы `M Mᵀ`.
+    /// `hradius` остаётся полуосью, ближайшей к образу прежней
+    fn transform(&mut self, affine: &Affine2) {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let p = affine.apply_vector(&Point { x: self.hradius * cos, y: self.hradius * sin });
+        let q = affine.apply_vector(&Point { x: -self.vradius * sin, y: self.vradius * cos });
+
+        let sxx = p.x * p.x + q.x * q.x;
+        let syy = p.y * p.y + q.y * q.y;
+        let sxy = p.x * p.y + q.x * q.y;
+        let mean = (sxx + syy) / 2.0;
+        let spread = ((sxx - syy) / 2.0).hypot(sxy);
+        let major = (mean + spread).sqrt();
+        let minor = (mean - spread).max(0.0).sqrt();
+        let major_angle = 0.5 * (2.0 * sxy).atan2(sxx - syy);
+
+        let (sin, cos) = major_angle.sin_cos();
+        let along = (p.x * cos + p.y * sin).abs();
+        let across = (p.y * cos - p.x * sin).abs();
+        let (mut angle, hradius, vradius) = if along >= across {
+            (major_angle, major, minor)
+        } else {
+            (major_angle + FRAC_PI_2, minor, major)
+        };
+        // Из двух направлений оси выбирается ближайшее к образу полуоси
+        let image_angle = p.y.atan2(p.x);
+        angle += PI * ((image_angle - angle) / PI).round();
+
+        self.center = affine.apply(&self.center);
+        self.hradius = hradius;
+        self.vradius = vradius;
+        self.angle = angle.to_degrees();
+    }
+}
+
+impl Validate for Ellipse {
+    /// # Проверка корректности эллипса: полуоси положительны
+    fn validate(&self) -> Result<(), ValidationError> {
+        check_numbers(&[self.center], &[self.hradius, self.vradius, self.angle])?;
+        if self.hradius.abs() > MAX_COORDINATE || self.vradius.abs() > MAX_COORDINATE {
+            return Err(ValidationError::Overflow);
+        }
+        if self.hradius < 0.0 || self.vradius < 0.0 {
+            return Err(ValidationError::NegativeRadius);
+        }
+        if self.hradius == 0.0 || self.vradius == 0.0 {
+            return Err(ValidationError::ZeroArea);
+        }
+        Ok(())
     }
 }
 
 impl FromStr for Ellipse {
-    type Err = ParseEllipseError;
+    type Err = Diagnostic;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tokens = s
-            .split_whitespace();
-
-        let x = tokens.next().ok_or(
-            ParseEllipseError {
-                place: s.to_string(),
-                cause: "No coords provided (need 2)".to_string(),
-            })?;
-        let y = tokens.next().ok_or(
-            ParseEllipseError {
-                place: s.to_string(),
-                cause: "Not enought coords (need 2)".to_string(),
-            })?;
-        let vradius = tokens.next().ok_or(
-            ParseEllipseError {
-                place: s.to_string(),
-                cause: "No vertical radius after coords".to_string(),
-            })?;
-        let hradius = tokens.next().ok_or(
-            ParseEllipseError {
-                place: s.to_string(),
-                cause: "No horizontal radius after coords".to_string(),
-            })?;
-
-        let x = x.parse::<f32>().map_err(
-            |_| ParseEllipseError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", x).to_string(),
-            })?;
-        let y = y.parse::<f32>().map_err(
-            |_| ParseEllipseError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", y).to_string(),
-            })?;
+        let mut params = Params::new("ELLIPSE", s, 4);
+
+        let x = params.next_float()?;
+        let y = params.next_float()?;
         let center = Point {x, y};
 
-        let vradius = vradius.parse::<f32>().map_err(
-            |_| ParseEllipseError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", vradius).to_string(),
-            })?;
-        let hradius = hradius.parse::<f32>().map_err(
-            |_| ParseEllipseError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", hradius).to_string(),
-            })?;
-
-        if hradius <= 0.0 || vradius <= 0.0 {
-            return Err(ParseEllipseError {
-                place: s.to_string(),
-                cause: "Invalid ellipse horisontal and vertical raidus must be greater then 0".to_string(),
-            })
-        }
+        let vradius = params.next_float()?;
+        let hradius = params.next_float()?;
 
-        Ok(Ellipse::new(center, vradius, hradius))
+        let ellipse = Ellipse::new(center, vradius, hradius);
+        ellipse.validate().map_err(|err| params.invalid(err))?;
+
+        Ok(ellipse)
     }
 }
 
@@ -175,5 +238,77 @@ mod tests {
         assert_eq!(2.0, ellipse.vradius);
         assert_eq!(4.0, ellipse.hradius);
     }
-}
 
+    #[test]
+    fn scale_xy_correct() {
+        let mut ellipse = Ellipse::new(Point{x:1.0, y:2.0}, 1.0, 2.0);
+
+        ellipse.scale_xy(3.0, 0.5);
+
+        assert_eq!(0.5, ellipse.vradius);
+        assert_eq!(6.0, ellipse.hradius);
+        assert_eq!(Point{x:1.0, y:2.0}, ellipse.center);
+    }
+
+    #[test]
+    fn get_frame_rect_rotated() {
+        let mut ellipse = Ellipse::new(Point{x:1.0, y:2.0}, 1.0, 2.0);
+
+        ellipse.rotate(90.0);
+        let rect = ellipse.get_frame_rect();
+
+        assert_relative_eq!(2.0, rect.width, epsilon = 1e-5);
+        assert_relative_eq!(4.0, rect.height, epsilon = 1e-5);
+        assert_eq!(Point{x:1.0, y:2.0}, rect.pos);
+    }
+
+    #[test]
+    fn rotate_about_correct() {
+        let mut ellipse = Ellipse::new(Point{x:1.0, y:0.0}, 1.0, 2.0);
+
+        ellipse.rotate_about(&Point{x:0.0, y:0.0}, 180.0);
+
+        assert_relative_eq!(-1.0, ellipse.center.x, epsilon = 1e-6);
+        assert_relative_eq!(0.0, ellipse.center.y, epsilon = 1e-6);
+        assert_eq!(180.0, ellipse.angle);
+    }
+
+    #[test]
+    fn transform_non_uniform_scale() {
+        let mut ellipse = Ellipse::new(Point{x:1.0, y:1.0}, 1.0, 1.0);
+
+        ellipse.transform(&Affine2::scaling(3.0, 2.0));
+
+        assert_eq!(Point{x:3.0, y:2.0}, ellipse.center);
+        assert_relative_eq!(3.0, ellipse.hradius, epsilon = 1e-6);
+        assert_relative_eq!(2.0, ellipse.vradius, epsilon = 1e-6);
+        assert_relative_eq!(0.0, ellipse.angle, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn transform_keeps_axes_on_rotation() {
+        let mut ellipse = Ellipse::new(Point{x:0.0, y:0.0}, 2.0, 1.0);
+
+        ellipse.transform(&Affine2::rotation(30.0));
+
+        assert_relative_eq!(1.0, ellipse.hradius, epsilon = 1e-5);
+        assert_relative_eq!(2.0, ellipse.vradius, epsilon = 1e-5);
+        assert_relative_eq!(30.0, ellipse.angle, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn transform_shear_of_rotated() {
+        let mut ellipse = Ellipse::new(Point{x:0.0, y:0.0}, 1.0, 2.0);
+        ellipse.rotate(45.0);
+        let affine = Affine2::shear(0.5, 0.0) * Affine2::scaling(2.0, 1.0);
+        let boundary = Point { x: 2.0, y: 0.0 }.rotated(&ellipse.center, 45.0);
+
+        ellipse.transform(&affine);
+
+        assert_relative_eq!(2.0 * PI * 2.0, ellipse.get_area(), epsilon = 1e-4);
+        // Образ точки границы остаётся на границе
+        let image = affine.apply(&boundary).rotated(&ellipse.center, -ellipse.angle);
+        let value = (image.x / ellipse.hradius).powi(2) + (image.y / ellipse.vradius).powi(2);
+        assert_relative_eq!(1.0, value, epsilon = 1e-4);
+    }
+}
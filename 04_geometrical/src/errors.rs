@@ -1,57 +1,212 @@
-
 use std::error;
 use std::fmt;
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseScaleCommandError {
-    pub place: String,
-    pub cause: String,
-}
+use crate::validate::ValidationError;
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseEllipseError {
-    pub place: String,
-    pub cause: String,
+///
+/// # Вид ошибки во входном файле
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Не хватает параметров команды
+    MissingParameter,
+    /// Число параметров не подходит ни под один вариант команды
+    WrongParameterCount,
+    /// Параметр не является числом с плавающей точкой
+    InvalidNumber,
+    /// Параметр не является номером фигуры
+    InvalidIndex,
+    /// Параметры разобраны, но задают некорректную фигуру
+    InvalidGeometry,
+    /// Неизвестное ключевое слово в начале строки
+    UnknownKeyword,
+    /// Команда ссылается на несуществующую фигуру
+    NoSuchShape,
+    /// Синтаксическая ошибка или неверная структура JSON
+    InvalidJson,
+    /// Нет операций для отмены или возврата
+    EmptyHistory,
+    /// `END` без `GROUP`, незакрытый или пустой `GROUP`
+    UnbalancedGroup,
+    /// Ключевое слово фигуры совпадает с командой сценария
+    ReservedKeyword,
+    /// Преобразование отклонено: фигура `index` стала бы некорректной
+    RejectedTransform { index: usize, error: ValidationError },
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseTriangleError {
-    pub place: String,
-    pub cause: String,
+///
+/// # Диагностика ошибки во входном файле
+///
+/// `line` и `column` нумеруются с единицы, `line` заполняется, когда
+/// известно положение строки в файле (см. [locate](#method.locate)).
+/// До этого `column` отсчитывается от начала параметров команды.
+/// `token` -- номер параметра, с которым связана ошибка.
+/// Тексты хранятся в `Box<str>`, чтобы `Result<_, Diagnostic>`
+/// оставался небольшим
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub keyword: &'static str,
+    pub place: Box<str>,
+    pub kind: ErrorKind,
+    pub cause: Box<str>,
+    pub line: Option<usize>,
+    pub column: usize,
+    pub token: Option<usize>,
+    pub suggestion: Option<&'static str>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseRectangleError {
-    pub place: String,
-    pub cause: String,
-}
+impl Diagnostic {
+    pub fn new(keyword: &'static str, place: &str, kind: ErrorKind, cause: String) -> Diagnostic {
+        Diagnostic {
+            keyword,
+            place: place.into(),
+            kind,
+            cause: cause.into_boxed_str(),
+            line: None,
+            column: 1,
+            token: None,
+            suggestion: None,
+        }
+    }
 
-impl error::Error for ParseEllipseError {}
-impl error::Error for ParseTriangleError {}
-impl error::Error for ParseRectangleError {}
-impl error::Error for ParseScaleCommandError {}
+    /// # Привязать ошибку к параметру с номером `token`
+    pub fn at_token(mut self, token: usize, column: usize) -> Diagnostic {
+        self.token = Some(token);
+        self.column = column;
+        self
+    }
 
-impl Display for ParseRectangleError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "For RECTANGLE cannot parse `{}': {}", self.place, self.cause)
+    /// # Привязать ошибку к строке файла
+    ///
+    /// `offset` -- число символов в строке перед параметрами команды
+    pub fn locate(mut self, line: usize, offset: usize) -> Diagnostic {
+        self.line = Some(line);
+        self.column += offset;
+        self
+    }
+
+    /// # Неизвестное ключевое слово с подсказкой из `known`
+    pub fn unknown_keyword(word: &str, known: &[&'static str]) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(
+            "",
+            word,
+            ErrorKind::UnknownKeyword,
+            "Unknown keyword".to_string(),
+        );
+        diagnostic.suggestion = suggest(word, known);
+        diagnostic
     }
 }
 
-impl Display for ParseTriangleError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "For TRIANGLE cannot parse `{}': {}", self.place, self.cause)
+/// Расстояние Левенштейна между строками
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
     }
+    previous[b.len()]
 }
 
-impl Display for ParseEllipseError {
+/// # Ближайшее к `word` слово из `known`
+///
+/// Регистр не учитывается, слишком непохожие слова не предлагаются
+pub fn suggest(word: &str, known: &[&'static str]) -> Option<&'static str> {
+    let word = word.to_uppercase();
+    known
+        .iter()
+        .map(|candidate| (levenshtein(&word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2 && *distance * 2 < word.chars().count())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| *candidate)
+}
+
+impl error::Error for Diagnostic {}
+
+impl Display for Diagnostic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "For ELLIPSE cannot parse `{}': {}", self.place, self.cause)
+        if let Some(line) = self.line {
+            write!(f, "line {line}, column {}: ", self.column)?;
+        }
+        match self.kind {
+            ErrorKind::UnknownKeyword => write!(f, "Unknown keyword `{}'", self.place)?,
+            ErrorKind::InvalidJson => write!(f, "For JSON scene: {}", self.cause)?,
+            ErrorKind::ReservedKeyword => write!(f, "Cannot register `{}': {}", self.place, self.cause)?,
+            ErrorKind::EmptyHistory | ErrorKind::UnbalancedGroup => {
+                write!(f, "For {}: {}", self.keyword, self.cause)?
+            },
+            ErrorKind::RejectedTransform { index, error } => {
+                write!(f, "For {} `{}': {} would make shape {index} invalid: {error}", self.keyword, self.place, self.keyword)?
+            },
+            ErrorKind::NoSuchShape => write!(f, "For {} `{}': {}", self.keyword, self.place, self.cause)?,
+            _ => write!(f, "For {} cannot parse `{}': {}", self.keyword, self.place, self.cause)?,
+        }
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ", did you mean {suggestion}?")?;
+        }
+        Ok(())
     }
 }
 
-impl Display for ParseScaleCommandError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "For SCALE cannot parse `{}': {}", self.place, self.cause)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYWORDS: [&str; 3] = ["RECTANGLE", "TRIANGLE", "ELLIPSE"];
+
+    #[test]
+    fn suggest_misspelled() {
+        assert_eq!(Some("ELLIPSE"), suggest("ELLLIPSE", &KEYWORDS));
+        assert_eq!(Some("RECTANGLE"), suggest("rectangel", &KEYWORDS));
+        assert_eq!(None, suggest("/*", &KEYWORDS));
+        assert_eq!(None, suggest("CIRCLE", &KEYWORDS));
+    }
+
+    #[test]
+    fn display_located() {
+        let diagnostic = Diagnostic::new(
+            "TRIANGLE",
+            "2 1 a",
+            ErrorKind::InvalidNumber,
+            "Cannot parse `a', expected float32".to_string(),
+        ).at_token(2, 5).locate(8, 9);
+
+        assert_eq!(
+            "line 8, column 14: For TRIANGLE cannot parse `2 1 a': Cannot parse `a', expected float32",
+            diagnostic.to_string(),
+        );
+    }
+
+    #[test]
+    fn display_unknown_keyword() {
+        let diagnostic = Diagnostic::unknown_keyword("ELLLIPSE", &KEYWORDS).locate(7, 0);
+
+        assert_eq!(
+            "line 7, column 1: Unknown keyword `ELLLIPSE', did you mean ELLIPSE?",
+            diagnostic.to_string(),
+        );
+    }
+
+    #[test]
+    fn display_rejected_transform() {
+        let error = ValidationError::Overflow;
+        let diagnostic = Diagnostic::new(
+            "SCALE",
+            "0 0 1e30",
+            ErrorKind::RejectedTransform { index: 0, error },
+            error.to_string(),
+        ).locate(3, 6);
+
+        assert_eq!(
+            "line 3, column 7: For SCALE `0 0 1e30': SCALE would make shape 0 invalid: coordinates are too large",
+            diagnostic.to_string(),
+        );
     }
 }
@@ -0,0 +1,177 @@
+use crate::base_types::{Float, Rect};
+
+///
+/// # Правило округления половины
+///
+/// `HalfUp` -- математическое округление: половина округляется от нуля
+/// (2.25 → 2.3, -2.25 → -2.3). `HalfEven` -- банковское: половина
+/// округляется к чётной цифре (2.25 → 2.2, 2.35 → 2.4)
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    HalfUp,
+    HalfEven,
+}
+
+///
+/// # Формат чисел в выводе
+///
+/// Округление выполняется над десятичной записью числа, а не над его
+/// двоичным значением: `0.15` округляется до `0.2`, хотя ближайшее
+/// к нему `f32` чуть меньше половины. Результат, равный нулю,
+/// выводится без знака
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Число знаков после десятичной точки
+    pub precision: usize,
+    pub rounding: Rounding,
+}
+
+impl NumberFormat {
+    pub fn new(precision: usize, rounding: Rounding) -> NumberFormat {
+        NumberFormat { precision, rounding }
+    }
+
+    /// # Число с заданной точностью
+    pub fn number(&self, value: Float) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        // Display для чисел с плавающей точкой даёт кратчайшую
+        // десятичную запись без экспоненты
+        let repr = value.abs().to_string();
+        let (int_part, frac_part) = repr.split_once('.').unwrap_or((&repr, ""));
+
+        let kept = frac_part.len().min(self.precision);
+        let mut digits: Vec<u8> = int_part
+            .bytes()
+            .chain(frac_part[..kept].bytes())
+            .map(|digit| digit - b'0')
+            .chain(std::iter::repeat_n(0, self.precision - kept))
+            .collect();
+
+        let rest = &frac_part[kept..];
+        if self.round_up(&digits, rest) {
+            increment(&mut digits);
+        }
+
+        let int_len = digits.len() - self.precision;
+        let mut text = String::new();
+        if value.is_sign_negative() && digits.iter().any(|&digit| digit != 0) {
+            text.push('-');
+        }
+        text.extend(digits[..int_len].iter().map(|&digit| char::from(b'0' + digit)));
+        if self.precision > 0 {
+            text.push('.');
+            text.extend(digits[int_len..].iter().map(|&digit| char::from(b'0' + digit)));
+        }
+        text
+    }
+
+    /// Нужно ли увеличить последнюю сохранённую цифру, `rest` -- отброшенные цифры
+    fn round_up(&self, digits: &[u8], rest: &str) -> bool {
+        let Some(first) = rest.bytes().next() else {
+            return false;
+        };
+        match (first - b'0').cmp(&5) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => match self.rounding {
+                Rounding::HalfUp => true,
+                Rounding::HalfEven => {
+                    rest.bytes().skip(1).any(|digit| digit != b'0')
+                        || digits.last().is_some_and(|digit| digit % 2 == 1)
+                },
+            },
+        }
+    }
+
+    /// # Прямоугольник в виде `x1 y1 x2 y2`: левый нижний и правый верхний углы
+    pub fn rect(&self, rect: &Rect) -> String {
+        let left_bot = rect.left_bot();
+        let right_top = rect.right_top();
+        [left_bot.x, left_bot.y, right_top.x, right_top.y]
+            .iter()
+            .map(|&value| self.number(value))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Один знак после запятой с математическим округлением, как требует задание
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new(1, Rounding::HalfUp)
+    }
+}
+
+/// Прибавить единицу к последней цифре с переносом
+fn increment(digits: &mut Vec<u8>) {
+    for digit in digits.iter_mut().rev() {
+        if *digit == 9 {
+            *digit = 0;
+        } else {
+            *digit += 1;
+            return;
+        }
+    }
+    digits.insert(0, 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_types::Point;
+
+    #[test]
+    fn half_up() {
+        let format = NumberFormat::default();
+
+        assert_eq!("378.5", format.number(378.49115));
+        assert_eq!("0.2", format.number(0.15));
+        assert_eq!("-2.3", format.number(-2.25));
+        assert_eq!("2.0", format.number(2.0));
+        assert_eq!("10.0", format.number(9.96));
+        assert_eq!("-1.0", format.number(-0.9999999));
+    }
+
+    #[test]
+    fn half_even() {
+        let format = NumberFormat::new(1, Rounding::HalfEven);
+
+        assert_eq!("2.2", format.number(2.25));
+        assert_eq!("2.4", format.number(2.35));
+        assert_eq!("2.3", format.number(2.2501));
+        assert_eq!("-0.2", format.number(-0.25));
+    }
+
+    #[test]
+    fn negative_zero() {
+        let format = NumberFormat::default();
+
+        assert_eq!("0.0", format.number(-0.0));
+        assert_eq!("0.0", format.number(-0.04));
+        assert_eq!("-0.1", format.number(-0.05));
+    }
+
+    #[test]
+    fn precision() {
+        assert_eq!("3", NumberFormat::new(0, Rounding::HalfUp).number(2.5));
+        assert_eq!("2", NumberFormat::new(0, Rounding::HalfEven).number(2.5));
+        assert_eq!("1.500", NumberFormat::new(3, Rounding::HalfUp).number(1.5));
+        assert_eq!("inf", NumberFormat::default().number(Float::INFINITY));
+    }
+
+    #[test]
+    fn rect_corners() {
+        let rect = Rect {
+            width: 2.0,
+            height: 1.0,
+            pos: Point { x: 0.0, y: -0.5 },
+        };
+
+        assert_eq!("-1.0 -1.0 1.0 0.0", NumberFormat::default().rect(&rect));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::shape::Shape;
+use crate::base_types::{Point, Rect, Float, ORIGIN};
+use crate::outline::Outline;
+use crate::affine::Affine2;
+use crate::validate::{Validate, ValidationError};
+
+///
+/// # Группа фигур
+///
+/// Ведёт себя как одна фигура: площадь -- сумма площадей частей
+/// (перекрытия считаются дважды), ограничивающий прямоугольник --
+/// объединение прямоугольников частей. Центр группы -- центр частей,
+/// взвешенный по площади; преобразования применяются ко всем частям
+/// относительно этого центра
+///
+#[derive(Debug, Clone, Default)]
+pub struct Group {
+    pub children: Vec<Box<dyn Shape>>,
+}
+
+impl Group {
+    pub fn new(children: Vec<Box<dyn Shape>>) -> Group {
+        Group { children }
+    }
+
+    pub fn push(&mut self, child: Box<dyn Shape>) {
+        self.children.push(child);
+    }
+
+    /// Перемещение части так, чтобы её центр удалился от `pivot`
+    /// в `sx` и `sy` раз по осям
+    fn spread(child: &mut dyn Shape, pivot: &Point, old_center: &Point, sx: Float, sy: Float) {
+        child.move_at(&Point {
+            x: pivot.x + (old_center.x - pivot.x) * sx,
+            y: pivot.y + (old_center.y - pivot.y) * sy,
+        });
+    }
+}
+
+impl Validate for Group {
+    /// # Все части корректны
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.children.iter().try_for_each(|child| child.validate())
+    }
+}
+
+impl Shape for Group {
+    fn get_area(&self) -> Float {
+        self.children.iter().map(|child| child.get_area()).sum()
+    }
+
+    fn get_frame_rect(&self) -> Rect {
+        if self.children.is_empty() {
+            return Rect { width: 0.0, height: 0.0, pos: self.get_center() };
+        }
+        let corners: Vec<Point> = self.children
+            .iter()
+            .map(|child| child.get_frame_rect())
+            .flat_map(|frame| [frame.left_bot(), frame.right_top()])
+            .collect();
+        Rect::bounding(&corners)
+    }
+
+    fn move_at(&mut self, target: &Point) {
+        let delta = *target - self.get_center();
+        self.move_by(delta.x, delta.y);
+    }
+
+    fn move_by(&mut self, x: Float, y: Float) {
+        for child in &mut self.children {
+            child.move_by(x, y);
+        }
+    }
+
+    fn scale(&mut self, factor: Float) {
+        let center = self.get_center();
+        for child in &mut self.children {
+            let old_center = child.get_center();
+            child.scale(factor);
+            Group::spread(child.as_mut(), &center, &old_center, factor, factor);
+        }
+    }
+
+    fn scale_xy(&mut self, sx: Float, sy: Float) {
+        let center = self.get_center();
+        for child in &mut self.children {
+            let old_center = child.get_center();
+            child.scale_xy(sx, sy);
+            Group::spread(child.as_mut(), &center, &old_center, sx, sy);
+        }
+    }
+
+    fn get_center(&self) -> Point {
+        let area = self.get_area();
+        if self.children.is_empty() || area == 0.0 {
+            return ORIGIN;
+        }
+        let mut center = ORIGIN;
+        for child in &self.children {
+            let weight = child.get_area() / area;
+            let child_center = child.get_center();
+            center.x += child_center.x * weight;
+            center.y += child_center.y * weight;
+        }
+        center
+    }
+
+    fn get_outline(&self) -> Outline {
+        Outline::Group(self.children.iter().map(|child| child.get_outline()).collect())
+    }
+
+    fn rotate(&mut self, angle: Float) {
+        let center = self.get_center();
+        for child in &mut self.children {
+            child.rotate_about(&center, angle);
+        }
+    }
+
+    fn transform(&mut self, affine: &Affine2) {
+        for child in &mut self.children {
+            child.transform(affine);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rectangle::Rectangle;
+    use crate::ellipse::Ellipse;
+    use crate::base_types::consts::PI;
+
+    fn group() -> Group {
+        Group::new(vec![
+            Box::new("0.0 0.0 2.0 2.0".parse::<Rectangle>().unwrap()),
+            Box::new("2.0 0.0 6.0 2.0".parse::<Rectangle>().unwrap()),
+        ])
+    }
+
+    #[test]
+    fn area_and_frame() {
+        let mut group = group();
+        group.push(Box::new("0.0 3.0 1.0 1.0".parse::<Ellipse>().unwrap()));
+
+        assert_relative_eq!(4.0 + 8.0 + PI, group.get_area(), epsilon = 1e-5);
+        assert_eq!(
+            Rect { pos: Point { x: 2.5, y: 2.0 }, width: 7.0, height: 4.0 },
+            group.get_frame_rect(),
+        );
+    }
+
+    #[test]
+    fn scale_about_group_center() {
+        let mut group = group();
+        // Центр, взвешенный по площади: (1 * 4 + 4 * 8) / 12 = 3
+        assert_eq!(Point { x: 3.0, y: 1.0 }, group.get_center());
+
+        group.scale(2.0);
+
+        let center = group.get_center();
+        assert_eq!(48.0, group.get_area());
+        assert_relative_eq!(3.0, center.x, epsilon = 1e-5);
+        assert_relative_eq!(1.0, center.y, epsilon = 1e-5);
+        assert_eq!(Point { x: -1.0, y: 1.0 }, group.children[0].get_center());
+    }
+
+    #[test]
+    fn rotate_keeps_relative_layout() {
+        let mut group = group();
+
+        group.rotate(90.0);
+        let first = group.children[0].get_center();
+        let second = group.children[1].get_center();
+
+        assert_relative_eq!(3.0, first.x, epsilon = 1e-5);
+        assert_relative_eq!(-1.0, first.y, epsilon = 1e-5);
+        assert_relative_eq!(3.0, second.x, epsilon = 1e-5);
+        assert_relative_eq!(2.0, second.y, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn outline_queries() {
+        let group = group();
+
+        assert!(group.contains_point(&Point { x: 5.0, y: 1.0 }));
+        assert!(!group.contains_point(&Point { x: 7.0, y: 1.0 }));
+        assert_eq!(8.0 + 12.0, group.get_perimeter());
+    }
+}
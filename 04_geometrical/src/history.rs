@@ -0,0 +1,348 @@
+use crate::base_types::{Float, Point};
+use crate::affine::Affine2;
+use crate::commands::Scale;
+use crate::shape::{Shape, dedup};
+use crate::validate::{ValidationError, validate_targets};
+
+///
+/// # Операция над набором фигур
+///
+/// `targets` -- номера фигур, к которым применяется операция.
+/// Несуществующие номера пропускаются, поэтому операцию можно
+/// повторить на другом наборе фигур
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// Сдвиг на `dx`, `dy`
+    MoveBy { targets: Vec<usize>, dx: Float, dy: Float },
+    /// Перенос центра в точку `target`
+    MoveAt { targets: Vec<usize>, target: Point },
+    /// Масштабирование командой `SCALE`
+    Scale { targets: Vec<usize>, scale: Scale },
+    /// Поворот вокруг точки `pivot` на `angle` градусов
+    Rotate { targets: Vec<usize>, pivot: Point, angle: Float },
+    /// Произвольное аффинное преобразование
+    Transform { targets: Vec<usize>, affine: Affine2 },
+    /// Удаление фигуры, следующие номера сдвигаются на единицу
+    Delete(usize),
+    /// Удаление фигур, совпадающих с предыдущими с допуском `epsilon`
+    Dedup { epsilon: Float },
+}
+
+impl Operation {
+    /// # Номера фигур, которые операция изменяет
+    ///
+    /// Удаление фигуры не изменяет, а только сдвигает номера
+    pub fn targets(&self) -> &[usize] {
+        match self {
+            Operation::MoveBy { targets, .. }
+            | Operation::MoveAt { targets, .. }
+            | Operation::Scale { targets, .. }
+            | Operation::Rotate { targets, .. }
+            | Operation::Transform { targets, .. } => targets,
+            Operation::Delete(_) | Operation::Dedup { .. } => &[],
+        }
+    }
+
+    pub fn apply(&self, shapes: &mut Vec<Box<dyn Shape>>) {
+        match self {
+            // Опора масштабирования может зависеть от всей сцены
+            Operation::Scale { targets, scale } => {
+                scale.apply_all(shapes, targets);
+                return;
+            },
+            Operation::Delete(index) => {
+                if *index < shapes.len() {
+                    shapes.remove(*index);
+                }
+                return;
+            },
+            Operation::Dedup { epsilon } => {
+                dedup(shapes, *epsilon);
+                return;
+            },
+            _ => {},
+        }
+
+        for &i in self.targets() {
+            let Some(shape) = shapes.get_mut(i) else {
+                continue;
+            };
+            match self {
+                Operation::MoveBy { dx, dy, .. } => shape.move_by(*dx, *dy),
+                Operation::MoveAt { target, .. } => shape.move_at(target),
+                Operation::Rotate { pivot, angle, .. } => shape.rotate_about(pivot, *angle),
+                Operation::Transform { affine, .. } => shape.transform(affine),
+                Operation::Scale { .. } | Operation::Delete(_) | Operation::Dedup { .. } => unreachable!("handled above"),
+            }
+        }
+    }
+}
+
+///
+/// # История преобразований набора фигур
+///
+/// Хранит исходные фигуры и список применённых операций. Отмена
+/// не обращает операцию, а заново применяет все предыдущие к копии
+/// исходных фигур, поэтому не накапливает ошибок округления
+///
+pub struct History {
+    originals: Vec<Box<dyn Shape>>,
+    shapes: Vec<Box<dyn Shape>>,
+    operations: Vec<Operation>,
+    /// Сколько исходных фигур было, когда записывалась каждая операция:
+    /// фигуры, добавленные позже, операция не затрагивает
+    scopes: Vec<usize>,
+    /// Сколько операций из `operations` сейчас действует,
+    /// остальные можно вернуть через `redo`
+    applied: usize,
+}
+
+impl History {
+    pub fn new(shapes: Vec<Box<dyn Shape>>) -> History {
+        History {
+            originals: shapes.clone(),
+            shapes,
+            operations: Vec::new(),
+            scopes: Vec::new(),
+            applied: 0,
+        }
+    }
+
+    /// # Текущее состояние фигур
+    pub fn shapes(&self) -> &[Box<dyn Shape>] {
+        &self.shapes
+    }
+
+    /// # Действующие операции в порядке применения
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations[..self.applied]
+    }
+
+    /// # Добавить фигуру в конец набора
+    ///
+    /// Фигура считается исходной: отмена операций её не удаляет,
+    /// а операции, записанные до её добавления, её не затрагивают
+    pub fn push_shape(&mut self, shape: Box<dyn Shape>) {
+        self.originals.push(shape.clone());
+        self.shapes.push(shape);
+    }
+
+    /// # Применить и записать операцию
+    ///
+    /// Если хотя бы одна из изменённых фигур после операции некорректна,
+    /// например координаты переполнились при масштабировании, операция
+    /// не применяется и возвращаются номер фигуры и нарушенное правило.
+    /// После применения отменённые операции вернуть уже нельзя
+    pub fn apply(&mut self, operation: Operation) -> Result<(), (usize, ValidationError)> {
+        // Текущие фигуры -- все исходные после действующих операций,
+        // поэтому достаточно применить операцию к ним
+        let mut shapes = self.shapes.clone();
+        operation.apply(&mut shapes);
+        self.commit(shapes, &operation)?;
+        self.operations.truncate(self.applied);
+        self.scopes.truncate(self.applied);
+        self.operations.push(operation);
+        self.scopes.push(self.originals.len());
+        self.applied += 1;
+        Ok(())
+    }
+
+    /// Сделать `shapes` текущими, если изменённые операцией фигуры корректны
+    fn commit(&mut self, shapes: Vec<Box<dyn Shape>>, operation: &Operation) -> Result<(), (usize, ValidationError)> {
+        validate_targets(&shapes, operation.targets())?;
+        self.shapes = shapes;
+        Ok(())
+    }
+
+    /// # Отменить последнюю операцию
+    ///
+    /// Возвращает `false`, если отменять нечего
+    pub fn undo(&mut self) -> bool {
+        if self.applied == 0 {
+            return false;
+        }
+        self.applied -= 1;
+        self.shapes = self.replay(&self.originals);
+        true
+    }
+
+    /// # Вернуть последнюю отменённую операцию
+    ///
+    /// Возвращает `Ok(false)`, если возвращать нечего. Операция заново
+    /// проверяется, как в `apply`: после отмены могли добавиться фигуры
+    pub fn redo(&mut self) -> Result<bool, (usize, ValidationError)> {
+        let Some(operation) = self.operations.get(self.applied).cloned() else {
+            return Ok(false);
+        };
+        let shapes = self.replay_first(&self.originals, self.applied + 1);
+        self.commit(shapes, &operation)?;
+        self.applied += 1;
+        Ok(true)
+    }
+
+    /// # Применить действующие операции к копии `originals`
+    ///
+    /// Каждая операция применяется только к фигурам, которые были
+    /// добавлены до её записи
+    pub fn replay(&self, originals: &[Box<dyn Shape>]) -> Vec<Box<dyn Shape>> {
+        self.replay_first(originals, self.applied)
+    }
+
+    /// Первые `count` операций, исходные фигуры добавляются по мере записи
+    fn replay_first(&self, originals: &[Box<dyn Shape>], count: usize) -> Vec<Box<dyn Shape>> {
+        let mut shapes = Vec::with_capacity(originals.len());
+        let mut added = 0;
+        for (operation, &scope) in self.operations[..count].iter().zip(&self.scopes) {
+            let scope = scope.clamp(added, originals.len());
+            shapes.extend_from_slice(&originals[added..scope]);
+            added = scope;
+            operation.apply(&mut shapes);
+        }
+        shapes.extend_from_slice(&originals[added..]);
+        shapes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rectangle::Rectangle;
+    use crate::ellipse::Ellipse;
+
+    fn scene() -> Vec<Box<dyn Shape>> {
+        vec![
+            Box::new("0.0 0.0 2.0 2.0".parse::<Rectangle>().unwrap()),
+            Box::new("5.0 5.0 1.0 2.0".parse::<Ellipse>().unwrap()),
+        ]
+    }
+
+    fn centers(shapes: &[Box<dyn Shape>]) -> Vec<Point> {
+        shapes.iter().map(|shape| shape.get_center()).collect()
+    }
+
+    #[test]
+    fn undo_restores_original() {
+        let mut history = History::new(scene());
+        history.apply(Operation::Scale {
+            targets: vec![0, 1],
+            scale: "1.0 1.0 3.0 0.5".parse().unwrap(),
+        }).unwrap();
+        history.apply(Operation::Rotate {
+            targets: vec![1],
+            pivot: Point { x: 0.0, y: 0.0 },
+            angle: 37.0,
+        }).unwrap();
+
+        assert!(history.undo());
+        assert!(history.undo());
+        assert!(!history.undo());
+
+        assert_eq!(centers(&scene()), centers(history.shapes()));
+        assert_eq!(4.0, history.shapes()[0].get_area());
+    }
+
+    #[test]
+    fn redo_after_undo() {
+        let mut history = History::new(scene());
+        history.apply(Operation::MoveBy { targets: vec![0], dx: 1.0, dy: 2.0 }).unwrap();
+        history.apply(Operation::Transform { targets: vec![1], affine: Affine2::shear(1.0, 0.0) }).unwrap();
+        history.apply(Operation::Delete(1)).unwrap();
+        let done = centers(history.shapes());
+
+        history.undo();
+        history.undo();
+        assert_eq!(2, history.shapes().len());
+        assert_eq!(Ok(true), history.redo());
+        assert_eq!(Ok(true), history.redo());
+        assert_eq!(Ok(false), history.redo());
+
+        assert_eq!(done, centers(history.shapes()));
+    }
+
+    #[test]
+    fn apply_discards_undone() {
+        let mut history = History::new(scene());
+        history.apply(Operation::MoveBy { targets: vec![0], dx: 1.0, dy: 0.0 }).unwrap();
+        history.undo();
+        history.apply(Operation::MoveAt { targets: vec![1], target: Point { x: 0.0, y: 0.0 } }).unwrap();
+
+        assert_eq!(Ok(false), history.redo());
+        assert_eq!(1, history.operations().len());
+        assert_eq!(Point { x: 1.0, y: 1.0 }, history.shapes()[0].get_center());
+    }
+
+    #[test]
+    fn replay_onto_copy() {
+        let mut history = History::new(scene());
+        history.apply(Operation::Delete(0)).unwrap();
+        history.apply(Operation::MoveBy { targets: vec![0, 5], dx: -5.0, dy: -5.0 }).unwrap();
+
+        let replayed = history.replay(&scene());
+
+        assert_eq!(centers(history.shapes()), centers(&replayed));
+        assert_eq!(Point { x: 0.0, y: 0.0 }, replayed[0].get_center());
+    }
+
+    #[test]
+    fn pushed_shape_survives_undo() {
+        let mut history = History::new(Vec::new());
+        history.push_shape(scene().remove(0));
+        history.apply(Operation::Delete(0)).unwrap();
+        history.push_shape(scene().remove(1));
+
+        history.undo();
+
+        assert_eq!(2, history.shapes().len());
+        assert_eq!(Point { x: 5.0, y: 5.0 }, history.shapes()[1].get_center());
+    }
+
+    #[test]
+    fn invalid_result_is_rejected() {
+        let mut history = History::new(scene());
+
+        let overflow = history.apply(Operation::Scale {
+            targets: vec![0, 1],
+            scale: "0 0 1e20".parse().unwrap(),
+        });
+        let collapse = history.apply(Operation::Scale {
+            targets: vec![1],
+            scale: "CENTER 1 0".parse().unwrap(),
+        });
+
+        assert_eq!(Err((0, ValidationError::Overflow)), overflow);
+        assert_eq!(Err((1, ValidationError::ZeroArea)), collapse);
+        assert!(history.operations().is_empty());
+        assert_eq!(4.0, history.shapes()[0].get_area());
+    }
+
+    #[test]
+    fn undo_keeps_shape_pushed_after_dedup() {
+        let mut history = History::new(scene());
+        history.apply(Operation::Dedup { epsilon: 1e-4 }).unwrap();
+        history.push_shape(scene().remove(0));
+        history.apply(Operation::MoveBy { targets: vec![2], dx: 1.0, dy: 0.0 }).unwrap();
+
+        history.undo();
+
+        assert_eq!(3, history.shapes().len());
+        assert_eq!(Point { x: 1.0, y: 1.0 }, history.shapes()[2].get_center());
+    }
+
+    #[test]
+    fn scene_anchor_ignores_later_shapes() {
+        let mut history = History::new(scene());
+        history.apply(Operation::Scale { targets: vec![0], scale: "SCENE 2".parse().unwrap() }).unwrap();
+        let scaled = centers(history.shapes());
+        history.push_shape(Box::new("20 20 30 30".parse::<Rectangle>().unwrap()));
+        history.apply(Operation::MoveBy { targets: vec![2], dx: 1.0, dy: 1.0 }).unwrap();
+
+        history.undo();
+
+        assert_eq!(scaled[..], centers(history.shapes())[..2]);
+        assert_eq!(Point { x: 25.0, y: 25.0 }, history.shapes()[2].get_center());
+        history.undo();
+        history.redo().unwrap();
+        assert_eq!(scaled[..], centers(history.shapes())[..2]);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::base_types::{Float, Point, cross};
+use crate::ellipse::Ellipse;
+use crate::polygon::Polygon;
+use crate::rectangle::Rectangle;
+use crate::shape::Shape;
+
+///
+/// # Фигура, охватывающая всю сцену
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enclosing {
+    /// Выпуклая оболочка
+    Hull,
+    /// Прямоугольник наименьшей площади, не обязательно вдоль осей
+    Rectangle,
+    /// Наименьший охватывающий круг
+    Circle,
+}
+
+impl Enclosing {
+    /// # Построить охватывающую фигуру
+    ///
+    /// `None`, если фигур нет или все они вырождены в точку или отрезок
+    pub fn build(self, shapes: &[Box<dyn Shape>], tolerance: Float) -> Option<Box<dyn Shape>> {
+        let points = scene_points(shapes, tolerance);
+        match self {
+            Enclosing::Hull => hull_polygon(&points).map(|shape| Box::new(shape) as Box<dyn Shape>),
+            Enclosing::Rectangle => min_area_rectangle(&points).map(|shape| Box::new(shape) as Box<dyn Shape>),
+            Enclosing::Circle => min_enclosing_circle(&points).map(|shape| Box::new(shape) as Box<dyn Shape>),
+        }
+    }
+}
+
+/// # Вершины всех фигур
+///
+/// Эллипсы заменяются вписанными многоугольниками с отклонением не
+/// больше `tolerance`, поэтому охватывающие фигуры могут оказаться
+/// меньше точных на эту величину
+pub fn scene_points(shapes: &[Box<dyn Shape>], tolerance: Float) -> Vec<Point> {
+    shapes
+        .iter()
+        .flat_map(|shape| shape.get_outline().polygons(tolerance))
+        .flatten()
+        .collect()
+}
+
+///
+/// # Выпуклая оболочка точек
+///
+/// Вершины против часовой стрелки, начиная с самой левой нижней.
+/// Точки на сторонах оболочки не включаются (алгоритм Эндрю)
+///
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Нижняя цепочка слева направо, затем верхняя справа налево,
+    // последняя точка каждой цепочки -- первая точка другой
+    let mut hull = half_hull(points.iter());
+    hull.pop();
+    let mut upper = half_hull(points.iter().rev());
+    upper.pop();
+    hull.append(&mut upper);
+    hull
+}
+
+/// Цепочка оболочки, поворачивающая только налево
+fn half_hull<'a>(points: impl Iterator<Item = &'a Point>) -> Vec<Point> {
+    let mut chain: Vec<Point> = Vec::new();
+    for point in points {
+        while chain.len() >= 2 && cross(&chain[chain.len() - 2], &chain[chain.len() - 1], point) <= 0.0 {
+            chain.pop();
+        }
+        chain.push(*point);
+    }
+    chain
+}
+
+/// # Выпуклая оболочка как многоугольник
+///
+/// `None`, если все точки лежат на одной прямой
+pub fn hull_polygon(points: &[Point]) -> Option<Polygon> {
+    let hull = convex_hull(points);
+    (hull.len() >= 3).then(|| Polygon::new(hull))
+}
+
+///
+/// # Прямоугольник наименьшей площади, содержащий точки
+///
+/// Одна из сторон такого прямоугольника лежит на стороне выпуклой
+/// оболочки, поэтому перебираются направления её сторон
+///
+pub fn min_area_rectangle(points: &[Point]) -> Option<Rectangle> {
+    let hull = convex_hull(points);
+    if hull.len() < 3 {
+        return None;
+    }
+
+    let mut best: Option<(Float, Rectangle)> = None;
+    for (i, a) in hull.iter().enumerate() {
+        let b = hull[(i + 1) % hull.len()];
+        let edge = b - *a;
+        let length = edge.x.hypot(edge.y);
+        let u = Point { x: edge.x / length, y: edge.y / length };
+        let v = Point { x: -u.y, y: u.x };
+
+        let (mut u_min, mut u_max, mut v_min, mut v_max) = (Float::MAX, Float::MIN, Float::MAX, Float::MIN);
+        for point in &hull {
+            let (pu, pv) = (point.x * u.x + point.y * u.y, point.x * v.x + point.y * v.y);
+            u_min = u_min.min(pu);
+            u_max = u_max.max(pu);
+            v_min = v_min.min(pv);
+            v_max = v_max.max(pv);
+        }
+
+        let (width, height) = (u_max - u_min, v_max - v_min);
+        let area = width * height;
+        if best.as_ref().is_some_and(|(best_area, _)| *best_area <= area) {
+            continue;
+        }
+        let (cu, cv) = ((u_min + u_max) / 2.0, (v_min + v_max) / 2.0);
+        let center = Point { x: u.x * cu + v.x * cv, y: u.y * cu + v.y * cv };
+        best = Some((area, Rectangle {
+            left_bot: Point { x: center.x - width / 2.0, y: center.y - height / 2.0 },
+            right_top: Point { x: center.x + width / 2.0, y: center.y + height / 2.0 },
+            angle: u.y.atan2(u.x).to_degrees(),
+            shear: 0.0,
+        }));
+    }
+    best.map(|(_, rectangle)| rectangle)
+}
+
+/// Круг как центр и квадрат радиуса
+type Circle = (Point, Float);
+
+fn distance2(a: &Point, b: &Point) -> Float {
+    (a.x - b.x).powi(2) + (a.y - b.y).powi(2)
+}
+
+fn covers(circle: &Circle, point: &Point) -> bool {
+    // Допуск на округление, иначе точки на окружности перестраивают круг
+    distance2(&circle.0, point) <= circle.1 * (1.0 + 1e-5)
+}
+
+fn diameter_circle(a: &Point, b: &Point) -> Circle {
+    let center = Point { x: (a.x + b.x) / 2.0, y: (a.y + b.y) / 2.0 };
+    (center, distance2(&center, a))
+}
+
+/// Описанная окружность, для точек на одной прямой -- круг на
+/// самой длинной стороне как на диаметре
+fn circumcircle(a: &Point, b: &Point, c: &Point) -> Circle {
+    let d = 2.0 * cross(a, b, c);
+    if d.abs() <= Float::EPSILON * distance2(a, b).max(distance2(a, c)) {
+        return [diameter_circle(a, b), diameter_circle(a, c), diameter_circle(b, c)]
+            .into_iter()
+            .max_by(|x, y| x.1.total_cmp(&y.1))
+            .expect("three candidates");
+    }
+    let (bx, by) = (b.x - a.x, b.y - a.y);
+    let (cx, cy) = (c.x - a.x, c.y - a.y);
+    let (b2, c2) = (bx * bx + by * by, cx * cx + cy * cy);
+    let center = Point {
+        x: a.x + (cy * b2 - by * c2) / d,
+        y: a.y + (bx * c2 - cx * b2) / d,
+    };
+    (center, distance2(&center, a))
+}
+
+///
+/// # Наименьший круг, содержащий точки
+///
+/// Алгоритм Велцля в итеративной форме. Точки берутся с выпуклой
+/// оболочки и перемешиваются, чтобы ожидаемое время было линейным
+///
+pub fn min_enclosing_circle(points: &[Point]) -> Option<Ellipse> {
+    let mut points = convex_hull(points);
+    if points.len() < 2 {
+        return None;
+    }
+    // Детерминированное перемешивание Фишера -- Йетса (xorshift)
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    for i in (1..points.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        points.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+
+    let mut circle: Circle = (points[0], 0.0);
+    for i in 1..points.len() {
+        if covers(&circle, &points[i]) {
+            continue;
+        }
+        circle = (points[i], 0.0);
+        for j in 0..i {
+            if covers(&circle, &points[j]) {
+                continue;
+            }
+            circle = diameter_circle(&points[i], &points[j]);
+            for k in 0..j {
+                if !covers(&circle, &points[k]) {
+                    circle = circumcircle(&points[i], &points[j], &points[k]);
+                }
+            }
+        }
+    }
+
+    let radius = circle.1.sqrt();
+    Some(Ellipse { center: circle.0, vradius: radius, hradius: radius, angle: 0.0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_types::consts::PI;
+
+    fn points(coords: &[(Float, Float)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    #[test]
+    fn hull_drops_inner_and_collinear() {
+        let hull = convex_hull(&points(&[
+            (0.0, 0.0), (2.0, 0.0), (1.0, 0.0), (2.0, 2.0), (0.0, 2.0), (1.0, 1.0), (0.0, 0.0),
+        ]));
+
+        assert_eq!(points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]), hull);
+        assert!(hull_polygon(&points(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)])).is_none());
+    }
+
+    #[test]
+    fn rectangle_of_rotated_square() {
+        let mut square: Rectangle = "0 0 2 2".parse().unwrap();
+        square.rotate(30.0);
+        let shapes: Vec<Box<dyn Shape>> = vec![Box::new(square)];
+
+        let rectangle = min_area_rectangle(&scene_points(&shapes, 1e-3)).unwrap();
+
+        assert_relative_eq!(4.0, rectangle.get_area(), epsilon = 1e-4);
+        assert_relative_eq!(1.0, rectangle.get_center().x, epsilon = 1e-5);
+        assert_relative_eq!(1.0, rectangle.get_center().y, epsilon = 1e-5);
+        for point in scene_points(&shapes, 1e-3) {
+            assert!(rectangle.contains_point(&point));
+        }
+    }
+
+    #[test]
+    fn circle_through_corners() {
+        let circle = min_enclosing_circle(&points(&[
+            (0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (0.0, 3.0), (2.0, 1.0),
+        ])).unwrap();
+
+        assert_relative_eq!(2.0, circle.center.x, epsilon = 1e-5);
+        assert_relative_eq!(1.5, circle.center.y, epsilon = 1e-5);
+        assert_relative_eq!(2.5, circle.hradius, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn enclose_scene() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new("0 0 1 1".parse::<Ellipse>().unwrap()),
+            Box::new("4 0 1 1".parse::<Ellipse>().unwrap()),
+        ];
+
+        let hull = Enclosing::Hull.build(&shapes, 1e-4).unwrap();
+        let rectangle = Enclosing::Rectangle.build(&shapes, 1e-4).unwrap();
+        let circle = Enclosing::Circle.build(&shapes, 1e-4).unwrap();
+
+        assert_relative_eq!(PI + 8.0, hull.get_area(), epsilon = 1e-2);
+        assert_relative_eq!(12.0, rectangle.get_area(), epsilon = 1e-2);
+        assert_relative_eq!(PI * 9.0, circle.get_area(), epsilon = 1e-2);
+        assert!(Enclosing::Circle.build(&[], 1e-4).is_none());
+    }
+}
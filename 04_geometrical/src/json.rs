@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+use crate::base_types::{Float, Point, Rect, Moments};
+use crate::commands::Scale;
+use crate::ellipse::Ellipse;
+use crate::errors::{Diagnostic, ErrorKind};
+use crate::polygon::Polygon;
+use crate::rectangle::Rectangle;
+use crate::shape::Shape;
+use crate::triangle::Triangle;
+use crate::union::union_area;
+
+///
+/// # Фигура в JSON
+///
+/// Тип фигуры задаётся полем `type` с тем же ключевым словом,
+/// что и в текстовом формате: `{"type": "ELLIPSE", "center": ...}`
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum ShapeData {
+    Rectangle(Rectangle),
+    Triangle(Triangle),
+    Ellipse(Ellipse),
+    Polygon(Polygon),
+}
+
+impl ShapeData {
+    /// # Проверить фигуру и превратить в `Box<dyn Shape>`
+    ///
+    /// JSON обходит `FromStr`, поэтому корректность проверяется здесь
+    /// по тем же правилам и с теми же сообщениями. `index` -- номер
+    /// фигуры в `shapes`, в диагностике он указывает на место ошибки
+    pub fn into_shape(self, index: usize) -> Result<Box<dyn Shape>, Diagnostic> {
+        let (keyword, shape): (&'static str, Box<dyn Shape>) = match self {
+            ShapeData::Rectangle(rectangle) => ("RECTANGLE", Box::new(rectangle)),
+            ShapeData::Triangle(triangle) => ("TRIANGLE", Box::new(triangle)),
+            ShapeData::Ellipse(ellipse) => ("ELLIPSE", Box::new(ellipse)),
+            ShapeData::Polygon(polygon) => ("POLYGON", Box::new(polygon)),
+        };
+        if let Err(err) = shape.validate() {
+            return Err(Diagnostic::new(
+                keyword,
+                &format!("shapes[{index}]"),
+                ErrorKind::InvalidGeometry,
+                err.to_string(),
+            ))
+        }
+        Ok(shape)
+    }
+}
+
+///
+/// # Сцена в JSON: фигуры и команда масштабирования
+///
+#[derive(Serialize, Deserialize)]
+pub struct SceneData {
+    pub shapes: Vec<ShapeData>,
+    pub scale: Scale,
+}
+
+impl SceneData {
+    /// # Разбор сцены из JSON
+    ///
+    /// Ошибки синтаксиса возвращаются с позицией в тексте
+    pub fn parse(source: &str) -> Result<SceneData, Diagnostic> {
+        serde_json::from_str(source).map_err(|err| {
+            let mut diagnostic = Diagnostic::new(
+                "JSON",
+                "",
+                ErrorKind::InvalidJson,
+                err.to_string(),
+            );
+            diagnostic.line = Some(err.line());
+            diagnostic.column = err.column().max(1);
+            diagnostic
+        })
+    }
+}
+
+///
+/// # Состояние фигур: суммарная площадь и ограничивающие прямоугольники
+///
+/// JSON-аналог строки вывода `area x1 y1 x2 y2 ...`
+///
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub area: Float,
+    pub frames: Vec<Rect>,
+}
+
+impl Report {
+    pub fn new(shapes: &[Box<dyn Shape>]) -> Report {
+        Report {
+            area: shapes.iter().map(|shape| shape.get_area()).sum(),
+            frames: shapes.iter().map(|shape| shape.get_frame_rect()).collect(),
+        }
+    }
+}
+
+///
+/// # Площадь для команды AREA: сумма площадей и площадь объединения
+///
+#[derive(Debug, Serialize)]
+pub struct Coverage {
+    pub sum: Float,
+    pub union: Float,
+}
+
+impl Coverage {
+    pub fn new(shapes: &[Box<dyn Shape>], tolerance: Float) -> Coverage {
+        Coverage {
+            sum: shapes.iter().map(|shape| shape.get_area()).sum(),
+            union: union_area(shapes, tolerance),
+        }
+    }
+}
+
+///
+/// # Характеристики одной фигуры для таблицы MEASURE
+///
+#[derive(Debug, Serialize)]
+pub struct Measurement {
+    pub area: Float,
+    pub perimeter: Float,
+    pub centroid: Point,
+    pub moments: Moments,
+}
+
+impl Measurement {
+    pub fn new(shape: &dyn Shape) -> Measurement {
+        Measurement {
+            area: shape.get_area(),
+            perimeter: shape.get_perimeter(),
+            centroid: shape.get_centroid(),
+            moments: shape.get_moments(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = r#"{
+        "shapes": [
+            {"type": "RECTANGLE", "left_bot": {"x": 1.0, "y": 1.0}, "right_top": {"x": 2.0, "y": 2.0}},
+            {"type": "ELLIPSE", "center": {"x": 0.0, "y": 0.0}, "vradius": 10.0, "hradius": 12.0, "angle": 0.0},
+            {"type": "TRIANGLE", "a": {"x": 0.0, "y": 0.0}, "b": {"x": 1.0, "y": 1.0}, "c": {"x": 0.0, "y": 1.0}}
+        ],
+        "scale": {"origin": {"x": 1.0, "y": 1.0}, "factor": 2.0}
+    }"#;
+
+    #[test]
+    fn parse_scene() {
+        let scene = SceneData::parse(SCENE).unwrap();
+
+        assert_eq!(3, scene.shapes.len());
+        assert_eq!(2.0, scene.scale.factor);
+        assert_eq!(
+            ShapeData::Rectangle("1.0 1.0 2.0 2.0".parse().unwrap()),
+            scene.shapes[0],
+        );
+    }
+
+    #[test]
+    fn parse_syntax_error_position() {
+        let err = SceneData::parse("{\n  \"shapes\": [,]\n}").err().unwrap();
+
+        assert_eq!(ErrorKind::InvalidJson, err.kind);
+        assert_eq!(Some(2), err.line);
+    }
+
+    #[test]
+    fn into_shape_rejects_invalid() {
+        let data: ShapeData = serde_json::from_str(
+            r#"{"type": "ELLIPSE", "center": {"x": 0.0, "y": 0.0}, "vradius": -1.0, "hradius": 2.0}"#,
+        ).unwrap();
+
+        let err = data.into_shape(3).err().unwrap();
+        let text = "0.0 0.0 -1.0 2.0".parse::<Ellipse>().err().unwrap();
+
+        assert_eq!(ErrorKind::InvalidGeometry, err.kind);
+        assert_eq!("ELLIPSE", err.keyword);
+        assert_eq!(text.cause, err.cause);
+        assert_eq!("For ELLIPSE cannot parse `shapes[3]': radii must not be negative", err.to_string());
+    }
+
+    #[test]
+    fn report_serialization() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new("0.0 0.0 2.0 1.0".parse::<Rectangle>().unwrap()),
+        ];
+
+        let json = serde_json::to_string(&Report::new(&shapes)).unwrap();
+
+        assert_eq!(
+            r#"{"area":2.0,"frames":[{"width":2.0,"height":1.0,"pos":{"x":1.0,"y":0.5}}]}"#,
+            json,
+        );
+    }
+
+    #[test]
+    fn shape_data_round_trip() {
+        let data = ShapeData::Polygon("0 0 2 0 2 2 0 2".parse().unwrap());
+
+        let json = serde_json::to_string(&data).unwrap();
+
+        assert!(json.starts_with(r#"{"type":"POLYGON","vertices":"#));
+        assert_eq!(data, serde_json::from_str(&json).unwrap());
+    }
+}
@@ -1,12 +1,29 @@
 
+#[cfg(test)]
 #[macro_use]
 extern crate approx;
 
 pub mod commands;
 pub mod errors;
+mod parse;
 pub mod rectangle;
 pub mod shape;
 pub mod base_types;
+pub mod affine;
 pub mod ellipse;
 pub mod triangle;
+pub mod polygon;
+pub mod group;
+pub mod outline;
+pub mod svg;
+pub mod json;
+pub mod format;
+pub mod spatial;
+pub mod history;
+pub mod union;
+pub mod scene;
+pub mod registry;
+pub mod raster;
+pub mod hull;
+pub mod validate;
 
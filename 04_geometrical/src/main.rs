@@ -1,13 +1,23 @@
 use geometrical::shape::Shape;
-use geometrical::base_types::{Point, Rect};
-use geometrical::rectangle::Rectangle;
-use geometrical::triangle::Triangle;
-use geometrical::ellipse::Ellipse;
+use geometrical::svg::Svg;
+use geometrical::errors::{Diagnostic, ErrorKind};
+use geometrical::json::{SceneData, Report, Measurement, Coverage};
+use geometrical::format::{NumberFormat, Rounding};
+use geometrical::scene::{Scene, Event};
+use geometrical::raster::{Image, PALETTE};
 
-use geometrical::commands::Scale;
+use geometrical::commands::{Area, Frame};
 
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+
+use std::env;
+use std::fs;
 use std::io;
 use std::io::BufRead;
+use std::io::IsTerminal;
+use std::io::BufWriter;
+use std::io::Read;
 use std::error::Error;
 use std::process;
 
@@ -31,98 +41,261 @@ fn main() {
  *
  */
 
-fn run() -> Result<(), Box<dyn Error>> {
-    let stdin = io::stdin().lock();
-    let lines: Vec<String> = stdin
-        .lines()
-        .collect::<Result<Vec<_>, _>>()
-        .expect("Cannot read stdio");
-    let mut shapes: Vec<Box<dyn Shape>> = Vec::new();
-    let mut errors: Vec<Box<dyn Error>> = Vec::new();
-    let mut scale_command_present: bool = false;
-    let mut scale: Scale = Scale::new();
-    
-    for line in lines {
-        if line == "" { continue }
-        let Some((name, figure_str)) = line.split_once(' ') else { continue };
-        match name {
-            "RECTANGLE" => {
-                let rectangle: Rectangle = match figure_str.parse() {
-                    Ok(rect) => rect,
-                    Err(err) => {
-                        errors.push(Box::new(err));
-                        continue;
-                    },
-                };
-                shapes.push(Box::new(rectangle));
-            },
-            "TRIANGLE" => { 
-                let triangle: Triangle = match figure_str.parse() {
-                    Ok(rect) => rect,
-                    Err(err) => {
-                        errors.push(Box::new(err));
-                        continue;
-                    },
-                };
-                shapes.push(Box::new(triangle));
-            },
-            "ELLIPSE" => {
-                let ellipse: Ellipse = match figure_str.parse() {
-                    Ok(rect) => rect,
-                    Err(err) => {
-                        errors.push(Box::new(err));
-                        continue;
-                    },
-                };
-                shapes.push(Box::new(ellipse));
+/*
+ *  Входной файл -- сценарий, строки которого выполняются по порядку:
+ *  – RECTANGLE, TRIANGLE, ELLIPSE, POLYGON добавляют фигуру;
+ *  – фигуры между GROUP и END объединяются в одну фигуру-группу,
+ *    группы могут быть вложенными;
+ *  – SCALE x y factor [factor_y] масштабирует выбранные фигуры и выводит
+ *    состояние до и после, factor_y задаёт отдельный коэффициент по оси y;
+ *    SCALE CENTER|CORNER|SCENE factor [factor_y] масштабирует каждую фигуру
+ *    относительно её центра, левого нижнего угла её ограничивающего
+ *    прямоугольника или центра ограничивающего прямоугольника всех фигур;
+ *  – MOVE dx dy сдвигает выбранные фигуры;
+ *  – ROTATE x y degrees поворачивает выбранные фигуры вокруг точки;
+ *  – PRINT выводит суммарную площадь и ограничивающие прямоугольники;
+ *  – MEASURE выводит таблицу площадей, периметров, центров масс и
+ *    моментов инерции фигур;
+ *  – AREA [tolerance] выводит сумму площадей фигур и площадь их объединения,
+ *    в которой перекрытия учитываются один раз; tolerance -- допустимое
+ *    отклонение при замене эллипсов многоугольниками;
+ *  – DELETE i удаляет фигуру с номером i (нумерация с нуля);
+ *  – DEDUP [epsilon] удаляет фигуры, совпадающие с одной из предыдущих
+ *    с допуском epsilon независимо от порядка вершин и способа записи;
+ *  – SELECT i j ... выбирает фигуры для следующих команд, SELECT без
+ *    параметров выбирает все фигуры;
+ *  – INTERSECTS i j выводит, пересекаются ли фигуры i и j;
+ *  – CONTAINS i x y выводит, содержит ли фигура i точку (x, y),
+ *    CONTAINS i j -- содержит ли фигура i фигуру j целиком;
+ *  – ENCLOSE HULL|RECT|CIRCLE [tolerance] добавляет фигуру, охватывающую
+ *    выбранные фигуры: выпуклую оболочку, прямоугольник наименьшей площади
+ *    или наименьший круг;
+ *  – UNDO отменяет последнюю из команд SCALE, MOVE, ROTATE, DELETE, DEDUP,
+ *    REDO возвращает отменённую команду.
+ *
+ *  Каждая команда действует только на фигуры, описанные до неё.
+ *
+ */
+
+/// Формат входных или выходных данных
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+fn parse_format(name: Option<String>) -> Result<Format, Box<dyn Error>> {
+    match name.as_deref() {
+        Some("text") => Ok(Format::Text),
+        Some("json") => Ok(Format::Json),
+        Some(other) => Err(format!("Unknown format `{other}', expected text or json").into()),
+        None => Err("Format expected: text or json".into()),
+    }
+}
+
+fn parse_rounding(name: Option<String>) -> Result<Rounding, Box<dyn Error>> {
+    match name.as_deref() {
+        Some("half-up") => Ok(Rounding::HalfUp),
+        Some("half-even") => Ok(Rounding::HalfEven),
+        Some(other) => Err(format!("Unknown rounding `{other}', expected half-up or half-even").into()),
+        None => Err("Rounding expected: half-up or half-even".into()),
+    }
+}
+
+fn parse_image_size(size: Option<String>) -> Result<(usize, usize), Box<dyn Error>> {
+    let size = size.ok_or("--image-size expects WIDTHxHEIGHT")?;
+    let parsed = size
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+    match parsed {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!("Cannot parse image size `{size}', expected WIDTHxHEIGHT").into()),
+    }
+}
+
+/// Параметры командной строки
+///
+/// `--svg FILE` -- сохранить фигуры в SVG: слои до и после каждого SCALE,
+/// `--svg-frames` -- дополнительно нарисовать ограничивающие прямоугольники,
+/// `--input json` -- читать сцену в JSON вместо сценария,
+/// `--output json` -- выводить результаты в JSON, по объекту на строку,
+/// `--precision N` -- знаков после запятой в текстовом выводе (по умолчанию 1),
+/// `--rounding half-up|half-even` -- математическое (по умолчанию) или банковское округление,
+/// `--png FILE`, `--ppm FILE` -- нарисовать фигуры после выполнения сценария,
+/// `--image-size WIDTHxHEIGHT` -- размер рисунка в пикселях (по умолчанию 800x600),
+/// `--repl` -- интерактивный режим, включается и без параметра, если ввод -- терминал
+struct Options {
+    repl: bool,
+    svg: Option<String>,
+    svg_frames: bool,
+    png: Option<String>,
+    ppm: Option<String>,
+    image_size: (usize, usize),
+    input: Format,
+    output: Format,
+    numbers: NumberFormat,
+}
+
+fn parse_args() -> Result<Options, Box<dyn Error>> {
+    let mut options = Options {
+        repl: false,
+        svg: None,
+        svg_frames: false,
+        png: None,
+        ppm: None,
+        image_size: (800, 600),
+        input: Format::Text,
+        output: Format::Text,
+        numbers: NumberFormat::default(),
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--svg" => {
+                let path = args.next().ok_or("--svg expects a file name")?;
+                options.svg = Some(path);
             },
-            "SCALE"=> {
-                scale = match figure_str.parse() {
-                    Ok(some) => some,
-                    Err(err) => {
-                        eprintln!("{err}");
-                        break;
-                    }
-                };
-                scale_command_present = true;
-                break;
+            "--svg-frames" => options.svg_frames = true,
+            "--repl" => options.repl = true,
+            "--png" => options.png = Some(args.next().ok_or("--png expects a file name")?),
+            "--ppm" => options.ppm = Some(args.next().ok_or("--ppm expects a file name")?),
+            "--image-size" => options.image_size = parse_image_size(args.next())?,
+            "--input" => options.input = parse_format(args.next())?,
+            "--output" => options.output = parse_format(args.next())?,
+            "--precision" => {
+                let precision = args.next().ok_or("--precision expects a number of digits")?;
+                options.numbers.precision = precision
+                    .parse()
+                    .map_err(|_| format!("Cannot parse precision `{precision}'"))?;
             },
-            _ => continue,
+            "--rounding" => options.numbers.rounding = parse_rounding(args.next())?,
+            _ => return Err(format!("Unknown argument `{arg}'").into()),
         }
     }
+    Ok(options)
+}
 
-    if !scale_command_present {
-        eprintln!("SCALE command expected!");
-        process::exit(1);
+fn print_state(shapes: &[Box<dyn Shape>], options: &Options) {
+    if options.output == Format::Json {
+        println!("{}", serde_json::to_string(&Report::new(shapes)).expect("Report is serializable"));
+        return;
     }
-
     let mut area = 0.0;
-    for shape in &shapes {
+    for shape in shapes {
         area += shape.get_area();
     }
-    print!("{area}");
-    for shape in &shapes {
-        print!(" {}", shape.get_frame_rect());
+    print!("{}", options.numbers.number(area));
+    for shape in shapes {
+        print!(" {}", options.numbers.rect(&shape.get_frame_rect()));
     }
-    println!("");
+    println!();
+}
 
-    let mut area = 0.0;
-    for shape in &mut shapes {
-        let center = shape.get_center();
-        shape.scale(scale.factor);
-        shape.move_at(&scale.origin);
+fn print_measurements(shapes: &[Box<dyn Shape>], options: &Options) {
+    if options.output == Format::Json {
+        let measurements: Vec<Measurement> = shapes
+            .iter()
+            .map(|shape| Measurement::new(shape.as_ref()))
+            .collect();
+        println!("{}", serde_json::to_string(&measurements).expect("Measurement is serializable"));
+        return;
+    }
+    println!(
+        "{:>3} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "#", "area", "perimeter", "centroid_x", "centroid_y", "ix", "iy", "ixy",
+    );
+    for (i, shape) in shapes.iter().enumerate() {
+        let centroid = shape.get_centroid();
+        let moments = shape.get_moments();
+        let values = [
+            shape.get_area(),
+            shape.get_perimeter(),
+            centroid.x,
+            centroid.y,
+            moments.ix,
+            moments.iy,
+            moments.ixy,
+        ];
+        let columns: Vec<String> = values
+            .iter()
+            .map(|&value| format!("{:>12}", options.numbers.number(value)))
+            .collect();
+        println!("{:>3} {}", i, columns.join(" "));
+    }
+}
 
-        let mut offset: Point = center - scale.origin;
-        offset *= scale.factor;
-        shape.move_by(offset.x, offset.y);
+/// Сумма площадей и площадь объединения фигур
+fn print_coverage(shapes: &[Box<dyn Shape>], area: &Area, options: &Options) {
+    let coverage = Coverage::new(shapes, area.tolerance);
+    if options.output == Format::Json {
+        println!("{}", serde_json::to_string(&coverage).expect("Coverage is serializable"));
+        return;
+    }
+    println!("{} {}", options.numbers.number(coverage.sum), options.numbers.number(coverage.union));
+}
 
-        area += shape.get_area();
+/// Вывести результат строки сценария
+fn print_event(event: &Event, shapes: &[Box<dyn Shape>], options: &Options) {
+    match event {
+        Event::Scaled { before } => {
+            print_state(before, options);
+            print_state(shapes, options);
+        },
+        Event::Print => print_state(shapes, options),
+        Event::Measure => print_measurements(shapes, options),
+        Event::Area(area) => print_coverage(shapes, area, options),
+        Event::Answer(answer) => println!("{answer}"),
+        // Сценарий выводит только запрошенное
+        Event::Added { .. } => (),
+    }
+}
+
+/// Сохранить рисунки фигур, запрошенные в параметрах
+fn write_images(shapes: &[Box<dyn Shape>], options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.png.is_none() && options.ppm.is_none() {
+        return Ok(());
+    }
+    let (width, height) = options.image_size;
+    let image = Image::render(shapes, &PALETTE, width, height);
+    if let Some(path) = &options.png {
+        image.write_png(BufWriter::new(fs::File::create(path)?))?;
+    }
+    if let Some(path) = &options.ppm {
+        image.write_ppm(BufWriter::new(fs::File::create(path)?))?;
+    }
+    Ok(())
+}
+
+/// Обработка сцены в JSON: вывод до и после масштабирования
+fn run_json(options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
+    let data = SceneData::parse(&source)?;
+
+    let mut scene = Scene::new();
+    let mut errors: Vec<Diagnostic> = Vec::new();
+    for (index, shape) in data.shapes.into_iter().enumerate() {
+        match shape.into_shape(index) {
+            Ok(shape) => scene.add_shape(shape),
+            Err(err) => errors.push(err),
+        }
     }
-    print!("{area}");
-    for shape in &shapes {
-        print!(" {}", shape.get_frame_rect());
+
+    let mut svg = Svg::new().with_frames(options.svg_frames);
+    print_state(scene.shapes(), options);
+    svg.add_layer("scale-1-before", scene.shapes());
+
+    if let Err(err) = scene.apply(data.scale) {
+        errors.push(err);
     }
-    println!("");
+
+    print_state(scene.shapes(), options);
+    svg.add_layer("scale-1-after", scene.shapes());
+
+    if let Some(path) = &options.svg {
+        fs::write(path, svg.to_string())?;
+    }
+    write_images(scene.shapes(), options)?;
 
     for error in errors {
         eprintln!("{}", error);
@@ -131,5 +304,135 @@ fn run() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+const REPL_HELP: &str = "\
+Commands of the input file are executed immediately, e.g. RECTANGLE 0 0 2 1, SCALE 0 0 2.
+Additional commands:
+  LIST     list shapes with their areas and frames
+  FRAME i  print the frame of shape i
+  HELP     print this help
+  QUIT     exit (as does Ctrl-D)";
+
+/// Строка списка фигур: номер, площадь, ограничивающий прямоугольник
+fn print_shape(index: usize, shape: &dyn Shape, options: &Options) {
+    println!(
+        "{index}: {} {}",
+        options.numbers.number(shape.get_area()),
+        options.numbers.rect(&shape.get_frame_rect()),
+    );
+}
+
+/// Интерактивный режим: строки выполняются сразу, ошибки выводятся
+/// после каждой строки
+fn run_repl(options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut editor = DefaultEditor::new()?;
+    let mut scene = Scene::new();
+    let mut number = 0;
+
+    loop {
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            // Ctrl-C сбрасывает набранную строку
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line)?;
+        number += 1;
+
+        let (name, params) = line.split_once(' ').unwrap_or((line, ""));
+        match name {
+            "QUIT" | "EXIT" => break,
+            "HELP" => println!("{REPL_HELP}"),
+            "LIST" => {
+                for (i, shape) in scene.shapes().iter().enumerate() {
+                    print_shape(i, shape.as_ref(), options);
+                }
+            },
+            "FRAME" => match params.parse::<Frame>() {
+                Ok(frame) => match scene.shapes().get(frame.index) {
+                    Some(shape) => println!("{}", options.numbers.rect(&shape.get_frame_rect())),
+                    None => eprintln!("{}", Diagnostic::new(
+                        "FRAME",
+                        params,
+                        ErrorKind::NoSuchShape,
+                        format!("No shape with index {}", frame.index),
+                    ).locate(number, name.chars().count() + 1)),
+                },
+                Err(err) => eprintln!("{}", err.locate(number, name.chars().count() + 1)),
+            },
+            _ => {
+                let errors = scene.errors().len();
+                match scene.execute(number, line) {
+                    // Подтверждение добавленной фигуры
+                    Some(Event::Added { index }) => print_shape(index, scene.shapes()[index].as_ref(), options),
+                    Some(event) => print_event(&event, scene.shapes(), options),
+                    None => (),
+                }
+                for error in &scene.errors()[errors..] {
+                    eprintln!("{}", error);
+                }
+            },
+        }
+    }
+
+    let reported = scene.errors().len();
+    scene.finish();
+    for error in &scene.errors()[reported..] {
+        eprintln!("{}", error);
+    }
+    write_images(scene.shapes(), options)
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let options = parse_args()?;
+    if options.input == Format::Json {
+        return run_json(&options);
+    }
+    if options.repl || io::stdin().is_terminal() {
+        return run_repl(&options);
+    }
+    let mut scene = Scene::new();
+    // Строка SCALE могла встретиться, но не выполниться из-за ошибки
+    let mut scale_command_present: bool = false;
+    let mut scale_applied: bool = false;
+    let mut svg = Svg::new().with_frames(options.svg_frames);
+    let mut scale_count = 0;
 
+    for (number, line) in io::stdin().lock().lines().enumerate() {
+        let line = line.expect("Cannot read stdio");
+        if line.split(' ').next() == Some("SCALE") {
+            scale_command_present = true;
+        }
+        let Some(event) = scene.execute(number + 1, &line) else {
+            continue;
+        };
+        if let Event::Scaled { before } = &event {
+            scale_applied = true;
+            scale_count += 1;
+            svg.add_layer(&format!("scale-{scale_count}-before"), before);
+            svg.add_layer(&format!("scale-{scale_count}-after"), scene.shapes());
+        }
+        print_event(&event, scene.shapes(), &options);
+    }
+    scene.finish();
+
+    for error in scene.errors() {
+        eprintln!("{}", error);
+    }
+    if !scale_applied {
+        if !scale_command_present {
+            eprintln!("SCALE command expected!");
+        }
+        process::exit(1);
+    }
 
+    if let Some(path) = &options.svg {
+        fs::write(path, svg.to_string())?;
+    }
+    write_images(scene.shapes(), &options)
+
+}
@@ -0,0 +1,965 @@
+use crate::base_types::{Point, Moments, Float, ORIGIN, EPS, cross, segments_intersect};
+use crate::base_types::consts::PI;
+
+///
+/// # Геометрическое описание контура фигуры
+///
+/// Позволяет работать с фигурами за `dyn Shape` без знания
+/// конкретного типа: рисовать, сравнивать, проверять пересечения
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outline {
+    /// Прямоугольник с центром `center`, повёрнутый на `angle` градусов
+    Rectangle {
+        center: Point,
+        width: Float,
+        height: Float,
+        angle: Float,
+    },
+    /// Многоугольник, заданный вершинами в порядке обхода
+    Polygon(Vec<Point>),
+    /// Эллипс с полуосями `hradius`, `vradius`, повёрнутый на `angle` градусов
+    Ellipse {
+        center: Point,
+        hradius: Float,
+        vradius: Float,
+        angle: Float,
+    },
+    /// Несколько контуров, рассматриваемых как одна фигура
+    Group(Vec<Outline>),
+}
+
+/// Относительный допуск для проверок на границе фигуры: расстояния
+/// сравниваются с `TOLERANCE`, умноженным на наибольшую координату
+/// участвующих фигур, см. `boundary_tolerance`
+const TOLERANCE: Float = 8.0 * Float::EPSILON;
+
+/// Допуск сравнения фигур по умолчанию, см. `Outline::approx_eq`
+pub const DEFAULT_EPSILON: Float = 1e-4;
+
+/// Наибольшее число сторон многоугольника, заменяющего эллипс
+const MAX_SEGMENTS: usize = 1 << 16;
+
+///
+/// Эллипс как образ единичного круга: `center + u * cos(t) + v * sin(t)`
+///
+#[derive(Debug, Clone, Copy)]
+struct EllipseFrame {
+    center: Point,
+    u: Point,
+    v: Point,
+}
+
+impl EllipseFrame {
+    fn new(center: Point, hradius: Float, vradius: Float, angle: Float) -> EllipseFrame {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        EllipseFrame {
+            center,
+            u: Point { x: hradius * cos, y: hradius * sin },
+            v: Point { x: -vradius * sin, y: vradius * cos },
+        }
+    }
+
+    /// Координаты вектора в базисе полуосей
+    fn unit_vector(&self, p: &Point) -> Point {
+        let det = self.u.x * self.v.y - self.v.x * self.u.y;
+        Point {
+            x: (p.x * self.v.y - self.v.x * p.y) / det,
+            y: (self.u.x * p.y - p.x * self.u.y) / det,
+        }
+    }
+
+    /// Координаты точки в пространстве, где эллипс -- единичный круг
+    fn unit_point(&self, p: &Point) -> Point {
+        self.unit_vector(&(*p - self.center))
+    }
+
+    /// Образ другого эллипса в пространстве единичного круга
+    fn unit_frame(&self, other: &EllipseFrame) -> EllipseFrame {
+        EllipseFrame {
+            center: self.unit_point(&other.center),
+            u: self.unit_vector(&other.u),
+            v: self.unit_vector(&other.v),
+        }
+    }
+
+    /// # Допуск в пространстве единичного круга
+    ///
+    /// Ошибка округления координат `points` и центра эллипса,
+    /// отнесённая к меньшей полуоси
+    fn unit_tolerance(&self, points: &[Point]) -> Float {
+        let radius = self.u.x.hypot(self.u.y).min(self.v.x.hypot(self.v.y));
+        let size = largest_coordinate(points.iter().chain([&self.center]));
+        TOLERANCE * (size / radius).max(1.0)
+    }
+
+    fn point_at(&self, t: Float) -> Point {
+        let (sin, cos) = t.sin_cos();
+        Point {
+            x: self.center.x + self.u.x * cos + self.v.x * sin,
+            y: self.center.y + self.u.y * cos + self.v.y * sin,
+        }
+    }
+
+    /// # Наименьшее или наибольшее расстояние от начала координат до контура
+    ///
+    /// Эллипс приводится к главным осям, после чего расстояние ищется
+    /// по условию Лагранжа (см. `axis_distance`). Ответ точен до
+    /// округления, в том числе для очень вытянутых эллипсов
+    #[allow(clippy::unnecessary_cast)] // Float может быть f64
+    fn distance_from_origin(&self, farthest: bool) -> Float {
+        let (u, v) = ((self.u.x as f64, self.u.y as f64), (self.v.x as f64, self.v.y as f64));
+        // Главные оси -- собственные векторы u uᵀ + v vᵀ, квадраты
+        // полуосей -- собственные значения
+        let (p, r, s) = (u.0 * u.0 + v.0 * v.0, u.0 * u.1 + v.0 * v.1, u.1 * u.1 + v.1 * v.1);
+        let mean = (p + s) / 2.0;
+        let spread = ((p - s) / 2.0).hypot(r);
+        let a = (mean + spread).sqrt();
+        let b = (mean - spread).max(0.0).sqrt();
+        let (sin, cos) = (0.5 * (2.0 * r).atan2(p - s)).sin_cos();
+
+        // Начало координат относительно центра в осях эллипса
+        let (x, y) = (-self.center.x as f64, -self.center.y as f64);
+        let px = (x * cos + y * sin).abs();
+        let py = (y * cos - x * sin).abs();
+        axis_distance(a, b, px, py, farthest) as Float
+    }
+}
+
+/// # Расстояние от точки `(px, py)` до эллипса `x²/a² + y²/b² = 1`
+///
+/// `a >= b > 0`, `px, py >= 0`. Ближайшая и дальняя точки контура --
+/// `x = a² px / (t + a²)`, `y = b² py / (t + b²)`, где `t` -- корень
+/// `(a px / (t + a²))² + (b py / (t + b²))² = 1`: единственный при
+/// `t > -b²` для ближайшей точки и при `t < -a²` для дальней.
+/// Корень находится делением пополам до соседних чисел с плавающей
+/// точкой. Точки на осях разбираются отдельно (Эберли)
+fn axis_distance(a: f64, b: f64, px: f64, py: f64, farthest: bool) -> f64 {
+    let (a2, b2) = (a * a, b * b);
+    let excess = |t: f64| (a * px / (t + a2)).powi(2) + (b * py / (t + b2)).powi(2) - 1.0;
+    let at = |t: f64| {
+        let x = if px > 0.0 { a2 * px / (t + a2) } else { 0.0 };
+        let y = if py > 0.0 { b2 * py / (t + b2) } else { 0.0 };
+        (x - px).hypot(y - py)
+    };
+    // За этим расстоянием от полюса `excess` не больше -3/4
+    let bound = 2.0 * (a * px).hypot(b * py);
+
+    if farthest {
+        if px > 0.0 {
+            return at(bisect(-a2 - bound, -a2, excess));
+        }
+        // На малой оси: d² = a² + py² - (a² - b²) s² - 2 b py s, s = sin θ
+        let sin = if a2 > b2 { (-b * py / (a2 - b2)).clamp(-1.0, 1.0) } else { -1.0 };
+        return (a2 + py * py - (a2 - b2) * sin * sin - 2.0 * b * py * sin).max(0.0).sqrt();
+    }
+
+    if py > 0.0 {
+        if px > 0.0 {
+            return at(bisect(-b2, -b2 + bound, excess));
+        }
+        return (py - b).abs();
+    }
+    if px * a < a2 - b2 {
+        let x = a2 * px / (a2 - b2);
+        let y = b * (1.0 - (x / a).powi(2)).max(0.0).sqrt();
+        return (x - px).hypot(y);
+    }
+    (px - a).abs()
+}
+
+/// Корень монотонной функции между `lo` и `hi`, где её знаки различны
+fn bisect(mut lo: f64, mut hi: f64, f: impl Fn(f64) -> f64) -> f64 {
+    let low_positive = f(lo) > 0.0;
+    for _ in 0..2200 {
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            break;
+        }
+        if (f(mid) > 0.0) == low_positive {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo + (hi - lo) / 2.0
+}
+
+/// Область, занимаемая фигурой
+enum Region {
+    Polygon(Vec<Point>),
+    Ellipse(EllipseFrame),
+}
+
+fn edges(vertices: &[Point]) -> impl Iterator<Item = (&Point, &Point)> {
+    vertices.iter().zip(vertices.iter().cycle().skip(1))
+}
+
+fn distance_to_segment(p: &Point, a: &Point, b: &Point) -> Float {
+    let ab = *b - *a;
+    let ap = *p - *a;
+    let len = ab.x * ab.x + ab.y * ab.y;
+    let t = if len > 0.0 {
+        ((ap.x * ab.x + ap.y * ab.y) / len).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let dx = ap.x - ab.x * t;
+    let dy = ap.y - ab.y * t;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Наибольшая по модулю координата точек
+fn largest_coordinate<'a>(points: impl IntoIterator<Item = &'a Point>) -> Float {
+    points.into_iter().fold(0.0, |size: Float, p| size.max(p.x.abs()).max(p.y.abs()))
+}
+
+/// # Допуск для проверок на границе многоугольников
+///
+/// Относителен, как и ошибка округления: для фигур размером 1e-6
+/// и для фигур вдали от начала координат ответы одинаково точны
+fn boundary_tolerance(a: &[Point], b: &[Point]) -> Float {
+    TOLERANCE * largest_coordinate(a.iter().chain(b))
+}
+
+/// Принадлежность точки многоугольнику, граница считается внутренней,
+/// если точка ближе к ней, чем на `tolerance`
+fn polygon_contains_point(vertices: &[Point], p: &Point, tolerance: Float) -> bool {
+    if edges(vertices).any(|(a, b)| distance_to_segment(p, a, b) <= tolerance) {
+        return true;
+    }
+
+    let mut inside = false;
+    for (a, b) in edges(vertices) {
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if p.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn polygons_intersect(a: &[Point], b: &[Point]) -> bool {
+    let tolerance = boundary_tolerance(a, b);
+    edges(a).any(|(p, q)| edges(b).any(|(c, d)| segments_intersect(p, q, c, d)))
+        || b.iter().any(|p| polygon_contains_point(a, p, tolerance))
+        || a.iter().any(|p| polygon_contains_point(b, p, tolerance))
+}
+
+/// Параметры точек на отрезке `pq`, в которых его пересекает отрезок `cd`
+fn crossing_params(p: &Point, q: &Point, c: &Point, d: &Point, tolerance: Float) -> Vec<Float> {
+    let r = *q - *p;
+    let s = *d - *c;
+    let denom = r.x * s.y - r.y * s.x;
+    let cp = *c - *p;
+
+    if denom.abs() <= EPS * r.x.hypot(r.y) * s.x.hypot(s.y) {
+        // Параллельные отрезки: важны только концы `cd`, лежащие на `pq`
+        let len = r.x * r.x + r.y * r.y;
+        return [c, d]
+            .iter()
+            .filter(|point| distance_to_segment(point, p, q) <= tolerance)
+            .map(|point| ((**point - *p).x * r.x + (**point - *p).y * r.y) / len)
+            .collect();
+    }
+
+    let t = (cp.x * s.y - cp.y * s.x) / denom;
+    let u = (cp.x * r.y - cp.y * r.x) / denom;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        vec![t]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Многоугольник `outer` содержит `inner`, если каждый кусок каждой
+/// стороны `inner` между точками пересечения с `outer` лежит внутри
+fn polygon_contains_polygon(outer: &[Point], inner: &[Point]) -> bool {
+    let tolerance = boundary_tolerance(outer, inner);
+    for (p, q) in edges(inner) {
+        let mut params = vec![0.0, 1.0];
+        for (c, d) in edges(outer) {
+            params.extend(crossing_params(p, q, c, d, tolerance));
+        }
+        params.sort_by(|a, b| a.total_cmp(b));
+
+        for pair in params.windows(2) {
+            let t = (pair[0] + pair[1]) / 2.0;
+            let mid = Point { x: p.x + (q.x - p.x) * t, y: p.y + (q.y - p.y) * t };
+            if !polygon_contains_point(outer, &mid, tolerance) {
+                return false;
+            }
+        }
+    }
+    inner.iter().all(|p| polygon_contains_point(outer, p, tolerance))
+}
+
+fn polygon_intersects_ellipse(vertices: &[Point], ellipse: &EllipseFrame) -> bool {
+    let unit: Vec<Point> = vertices.iter().map(|p| ellipse.unit_point(p)).collect();
+    let tolerance = ellipse.unit_tolerance(vertices);
+
+    polygon_contains_point(&unit, &ORIGIN, tolerance)
+        || edges(&unit).any(|(a, b)| distance_to_segment(&ORIGIN, a, b) <= 1.0 + tolerance)
+}
+
+fn polygon_contains_ellipse(vertices: &[Point], ellipse: &EllipseFrame) -> bool {
+    let unit: Vec<Point> = vertices.iter().map(|p| ellipse.unit_point(p)).collect();
+    let tolerance = ellipse.unit_tolerance(vertices);
+
+    polygon_contains_point(&unit, &ORIGIN, tolerance)
+        && edges(&unit).all(|(a, b)| distance_to_segment(&ORIGIN, a, b) >= 1.0 - tolerance)
+}
+
+fn ellipse_contains_point(ellipse: &EllipseFrame, p: &Point) -> bool {
+    let unit = ellipse.unit_point(p);
+    unit.x.hypot(unit.y) <= 1.0 + ellipse.unit_tolerance(&[*p])
+}
+
+/// Точки, по которым оценивается размер эллипса для допусков
+fn reach(ellipse: &EllipseFrame) -> [Point; 2] {
+    [ellipse.center, ellipse.center + ellipse.u + ellipse.v]
+}
+
+fn ellipses_intersect(a: &EllipseFrame, b: &EllipseFrame) -> bool {
+    let other = a.unit_frame(b);
+
+    ellipse_contains_point(b, &a.center)
+        || other.distance_from_origin(false) <= 1.0 + a.unit_tolerance(&reach(b))
+}
+
+fn ellipse_contains_ellipse(outer: &EllipseFrame, inner: &EllipseFrame) -> bool {
+    outer.unit_frame(inner).distance_from_origin(true) <= 1.0 + outer.unit_tolerance(&reach(inner))
+}
+
+/// Наибольшая по модулю координата, но не меньше 1: масштаб для
+/// допусков, относительных для больших координат
+fn magnitude(points: &[&Point]) -> Float {
+    largest_coordinate(points.iter().copied()).max(1.0)
+}
+
+fn points_close(a: &Point, b: &Point, epsilon: Float) -> bool {
+    (a.x - b.x).hypot(a.y - b.y) <= epsilon * magnitude(&[a, b])
+}
+
+/// Один и тот же многоугольник с точностью до начальной вершины
+/// и направления обхода
+fn polygons_equivalent(a: &[Point], b: &[Point], epsilon: Float) -> bool {
+    let n = a.len();
+    if n != b.len() {
+        return false;
+    }
+    (0..n).any(|shift| {
+        (0..n).all(|i| points_close(&a[i], &b[(shift + i) % n], epsilon))
+            || (0..n).all(|i| points_close(&a[i], &b[(shift + n - i) % n], epsilon))
+    })
+}
+
+/// Один и тот же эллипс: совпадают центры и матрицы `u uᵀ + v vᵀ`,
+/// которые не зависят от того, какая полуось названа горизонтальной
+fn ellipses_equivalent(a: &EllipseFrame, b: &EllipseFrame, epsilon: Float) -> bool {
+    let form = |e: &EllipseFrame| [
+        e.u.x * e.u.x + e.v.x * e.v.x,
+        e.u.x * e.u.y + e.v.x * e.v.y,
+        e.u.y * e.u.y + e.v.y * e.v.y,
+    ];
+    let radius = |e: &EllipseFrame| e.u.x.hypot(e.u.y).max(e.v.x.hypot(e.v.y));
+    // Сдвиг полуоси длины r на d меняет матрицу примерно на 2 r d
+    let size = magnitude(&[&a.center, &b.center]).max(radius(a)).max(radius(b));
+    points_close(&a.center, &b.center, epsilon)
+        && form(a)
+            .iter()
+            .zip(form(b))
+            .all(|(x, y)| (x - y).abs() <= 2.0 * epsilon * size * size)
+}
+
+fn polygon_perimeter(vertices: &[Point]) -> Float {
+    edges(vertices)
+        .map(|(a, b)| ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt())
+        .sum()
+}
+
+/// # Ориентированная площадь многоугольника (формула шнурования)
+///
+/// Положительна при обходе вершин против часовой стрелки. Векторные
+/// произведения считаются от первой вершины, чтобы маленький
+/// многоугольник вдали от начала координат не терял точность
+pub(crate) fn polygon_signed_area(vertices: &[Point]) -> Float {
+    let Some(first) = vertices.first() else {
+        return 0.0;
+    };
+    edges(vertices).map(|(p, q)| cross(first, p, q)).sum::<Float>() / 2.0
+}
+
+/// # Центр масс многоугольника
+pub(crate) fn polygon_centroid(vertices: &[Point]) -> Point {
+    let Some(&first) = vertices.first() else {
+        return ORIGIN;
+    };
+    let area = polygon_signed_area(vertices);
+    let mut cx = 0.0;
+    let mut cy = 0.0;
+    for (p, q) in edges(vertices) {
+        let (p, q) = (*p - first, *q - first);
+        let cross = p.x * q.y - q.x * p.y;
+        cx += (p.x + q.x) * cross;
+        cy += (p.y + q.y) * cross;
+    }
+    first + Point {
+        x: cx / (6.0 * area),
+        y: cy / (6.0 * area),
+    }
+}
+
+/// Моменты многоугольника: вершины сдвигаются в центр масс, чтобы
+/// не применять теорему Штейнера и не терять точность
+fn polygon_moments(vertices: &[Point]) -> Moments {
+    let centroid = polygon_centroid(vertices);
+    let local: Vec<Point> = vertices.iter().map(|p| *p - centroid).collect();
+    let orientation = polygon_signed_area(&local).signum();
+
+    let mut ix = 0.0;
+    let mut iy = 0.0;
+    let mut ixy = 0.0;
+    for (p, q) in edges(&local) {
+        let cross = p.x * q.y - q.x * p.y;
+        ix += cross * (p.y * p.y + p.y * q.y + q.y * q.y);
+        iy += cross * (p.x * p.x + p.x * q.x + q.x * q.x);
+        ixy += cross * (p.x * q.y + 2.0 * p.x * p.y + 2.0 * q.x * q.y + q.x * p.y);
+    }
+
+    Moments {
+        ix: orientation * ix / 12.0,
+        iy: orientation * iy / 12.0,
+        ixy: orientation * ixy / 24.0,
+    }
+}
+
+/// Периметр эллипса по второй формуле Рамануджана
+fn ellipse_perimeter(a: Float, b: Float) -> Float {
+    let h = (a - b).powi(2) / (a + b).powi(2);
+    PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+}
+
+fn ellipse_moments(ellipse: &EllipseFrame) -> Moments {
+    let hradius = ellipse.u.x.hypot(ellipse.u.y);
+    let vradius = ellipse.v.x.hypot(ellipse.v.y);
+    // Моменты относительно собственных осей эллипса
+    let along_x = PI * hradius * vradius.powi(3) / 4.0;
+    let along_y = PI * hradius.powi(3) * vradius / 4.0;
+    let sin = ellipse.u.y / hradius;
+    let cos = ellipse.u.x / hradius;
+
+    Moments {
+        ix: along_x * cos * cos + along_y * sin * sin,
+        iy: along_x * sin * sin + along_y * cos * cos,
+        ixy: (along_y - along_x) * sin * cos,
+    }
+}
+
+impl Outline {
+    fn region(&self) -> Region {
+        match self {
+            Outline::Rectangle { center, width, height, angle } => {
+                let (w, h) = (width / 2.0, height / 2.0);
+                Region::Polygon([(-w, -h), (w, -h), (w, h), (-w, h)]
+                    .iter()
+                    .map(|&(x, y)| Point { x: center.x + x, y: center.y + y }.rotated(center, *angle))
+                    .collect())
+            },
+            Outline::Polygon(vertices) => Region::Polygon(vertices.clone()),
+            Outline::Ellipse { center, hradius, vradius, angle } => {
+                Region::Ellipse(EllipseFrame::new(*center, *hradius, *vradius, *angle))
+            },
+            Outline::Group(_) => unreachable!("groups are handled part by part"),
+        }
+    }
+
+    /// Площадь; у группы перекрытия частей считаются дважды
+    fn area(&self) -> Float {
+        if let Outline::Group(parts) = self {
+            return parts.iter().map(Outline::area).sum();
+        }
+        match self.region() {
+            Region::Polygon(vertices) => polygon_signed_area(&vertices).abs(),
+            Region::Ellipse(ellipse) => {
+                PI * (ellipse.u.x * ellipse.v.y - ellipse.v.x * ellipse.u.y).abs()
+            },
+        }
+    }
+
+    /// # Многоугольники, приближающие фигуру
+    ///
+    /// Многоугольники и прямоугольники возвращаются точно, эллипс
+    /// заменяется вписанным многоугольником, стороны которого отстоят
+    /// от эллипса не больше чем на `tolerance`: чем крупнее эллипс и
+    /// меньше допуск, тем больше сторон. Группа даёт по многоугольнику
+    /// на каждую часть
+    pub fn polygons(&self, tolerance: Float) -> Vec<Vec<Point>> {
+        if let Outline::Group(parts) = self {
+            return parts.iter().flat_map(|part| part.polygons(tolerance)).collect();
+        }
+        match self.region() {
+            Region::Polygon(vertices) => vec![vertices],
+            Region::Ellipse(ellipse) => {
+                // Эллипс -- аффинный образ окружности, поэтому отклонение
+                // хорды от дуги не больше, чем у окружности с большой полуосью
+                let radius = ellipse.u.x.hypot(ellipse.u.y).max(ellipse.v.x.hypot(ellipse.v.y));
+                let step = if tolerance < radius {
+                    2.0 * (1.0 - tolerance / radius).acos()
+                } else {
+                    PI
+                };
+                let count = ((2.0 * PI / step).ceil() as usize).clamp(8, MAX_SEGMENTS);
+                vec![(0..count)
+                    .map(|i| ellipse.point_at(2.0 * PI * i as Float / count as Float))
+                    .collect()]
+            },
+        }
+    }
+
+    /// # Одна и та же ли фигура с допуском `epsilon`
+    ///
+    /// Сравнивается занятая фигурой область, а не способ её задания:
+    /// порядок и направление обхода вершин не важны, прямоугольник
+    /// равен многоугольнику с теми же углами, эллипс с переставленными
+    /// полуосями и углом, отличающимся на 90 градусов, -- исходному.
+    /// Группы равны, если части попарно равны в любом порядке.
+    /// Допуск -- расстояние между вершинами, для координат больше 1
+    /// он относительный
+    pub fn approx_eq(&self, other: &Outline, epsilon: Float) -> bool {
+        match (self, other) {
+            (Outline::Group(parts), Outline::Group(others)) => {
+                if parts.len() != others.len() {
+                    return false;
+                }
+                let mut matched = vec![false; others.len()];
+                parts.iter().all(|part| {
+                    let found = others
+                        .iter()
+                        .enumerate()
+                        .position(|(i, other)| !matched[i] && part.approx_eq(other, epsilon));
+                    match found {
+                        Some(i) => {
+                            matched[i] = true;
+                            true
+                        },
+                        None => false,
+                    }
+                })
+            },
+            (Outline::Group(_), _) | (_, Outline::Group(_)) => false,
+            _ => match (self.region(), other.region()) {
+                (Region::Polygon(a), Region::Polygon(b)) => polygons_equivalent(&a, &b, epsilon),
+                (Region::Ellipse(a), Region::Ellipse(b)) => ellipses_equivalent(&a, &b, epsilon),
+                _ => false,
+            },
+        }
+    }
+
+    /// # Принадлежность точки фигуре (включая границу)
+    pub fn contains_point(&self, point: &Point) -> bool {
+        if let Outline::Group(parts) = self {
+            return parts.iter().any(|part| part.contains_point(point));
+        }
+        match self.region() {
+            Region::Polygon(vertices) => {
+                polygon_contains_point(&vertices, point, boundary_tolerance(&vertices, &[*point]))
+            },
+            Region::Ellipse(ellipse) => ellipse_contains_point(&ellipse, point),
+        }
+    }
+
+    /// # Есть ли у фигур общие точки (касание считается пересечением)
+    pub fn intersects(&self, other: &Outline) -> bool {
+        if let Outline::Group(parts) = self {
+            return parts.iter().any(|part| part.intersects(other));
+        }
+        if let Outline::Group(parts) = other {
+            return parts.iter().any(|part| self.intersects(part));
+        }
+        match (self.region(), other.region()) {
+            (Region::Polygon(a), Region::Polygon(b)) => polygons_intersect(&a, &b),
+            (Region::Polygon(a), Region::Ellipse(b))
+                | (Region::Ellipse(b), Region::Polygon(a)) => polygon_intersects_ellipse(&a, &b),
+            (Region::Ellipse(a), Region::Ellipse(b)) => ellipses_intersect(&a, &b),
+        }
+    }
+
+    /// # Содержит ли фигура `other` целиком
+    ///
+    /// Группа содержит фигуру, если её содержит одна из частей:
+    /// фигура, лежащая сразу в нескольких частях, не распознаётся
+    pub fn contains(&self, other: &Outline) -> bool {
+        if let Outline::Group(parts) = other {
+            return parts.iter().all(|part| self.contains(part));
+        }
+        if let Outline::Group(parts) = self {
+            return parts.iter().any(|part| part.contains(other));
+        }
+        match (self.region(), other.region()) {
+            (Region::Polygon(a), Region::Polygon(b)) => polygon_contains_polygon(&a, &b),
+            (Region::Polygon(a), Region::Ellipse(b)) => polygon_contains_ellipse(&a, &b),
+            (Region::Ellipse(a), Region::Polygon(b)) => {
+                b.iter().all(|p| ellipse_contains_point(&a, p))
+            },
+            (Region::Ellipse(a), Region::Ellipse(b)) => ellipse_contains_ellipse(&a, &b),
+        }
+    }
+
+    /// # Периметр (для эллипса -- приближение Рамануджана)
+    ///
+    /// У группы -- сумма периметров частей
+    pub fn perimeter(&self) -> Float {
+        if let Outline::Group(parts) = self {
+            return parts.iter().map(Outline::perimeter).sum();
+        }
+        match self.region() {
+            Region::Polygon(vertices) => polygon_perimeter(&vertices),
+            Region::Ellipse(ellipse) => ellipse_perimeter(
+                ellipse.u.x.hypot(ellipse.u.y),
+                ellipse.v.x.hypot(ellipse.v.y),
+            ),
+        }
+    }
+
+    /// # Центр масс фигуры
+    pub fn centroid(&self) -> Point {
+        if let Outline::Group(parts) = self {
+            let area = self.area();
+            let mut centroid = ORIGIN;
+            for part in parts {
+                let weight = part.area() / area;
+                let part_centroid = part.centroid();
+                centroid.x += part_centroid.x * weight;
+                centroid.y += part_centroid.y * weight;
+            }
+            return centroid;
+        }
+        match self.region() {
+            Region::Polygon(vertices) => polygon_centroid(&vertices),
+            Region::Ellipse(ellipse) => ellipse.center,
+        }
+    }
+
+    /// # Моменты инерции площади относительно центра масс
+    ///
+    /// Моменты частей группы переносятся к общему центру масс
+    /// по теореме Штейнера
+    pub fn moments(&self) -> Moments {
+        if let Outline::Group(parts) = self {
+            let center = self.centroid();
+            let mut moments = Moments { ix: 0.0, iy: 0.0, ixy: 0.0 };
+            for part in parts {
+                let area = part.area();
+                let offset = part.centroid() - center;
+                let own = part.moments();
+                moments.ix += own.ix + area * offset.y * offset.y;
+                moments.iy += own.iy + area * offset.x * offset.x;
+                moments.ixy += own.ixy + area * offset.x * offset.y;
+            }
+            return moments;
+        }
+        match self.region() {
+            Region::Polygon(vertices) => polygon_moments(&vertices),
+            Region::Ellipse(ellipse) => ellipse_moments(&ellipse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rectangle(x: Float, y: Float, width: Float, height: Float) -> Outline {
+        Outline::Rectangle {
+            center: Point { x, y },
+            width,
+            height,
+            angle: 0.0,
+        }
+    }
+
+    fn circle(x: Float, y: Float, radius: Float) -> Outline {
+        Outline::Ellipse {
+            center: Point { x, y },
+            hradius: radius,
+            vradius: radius,
+            angle: 0.0,
+        }
+    }
+
+    fn triangle(points: [(Float, Float); 3]) -> Outline {
+        Outline::Polygon(points.iter().map(|&(x, y)| Point { x, y }).collect())
+    }
+
+    #[test]
+    fn contains_point_rectangle() {
+        let rect = rectangle(0.0, 0.0, 2.0, 2.0);
+
+        assert!(rect.contains_point(&Point { x: 0.5, y: -0.5 }));
+        assert!(rect.contains_point(&Point { x: 1.0, y: 1.0 }));
+        assert!(!rect.contains_point(&Point { x: 1.5, y: 0.0 }));
+    }
+
+    #[test]
+    fn contains_point_rotated_ellipse() {
+        let ellipse = Outline::Ellipse {
+            center: ORIGIN,
+            hradius: 4.0,
+            vradius: 1.0,
+            angle: 90.0,
+        };
+
+        assert!(ellipse.contains_point(&Point { x: 0.0, y: 3.5 }));
+        assert!(!ellipse.contains_point(&Point { x: 3.5, y: 0.0 }));
+    }
+
+    #[test]
+    fn intersects_rectangles() {
+        let a = rectangle(0.0, 0.0, 2.0, 2.0);
+
+        assert!(a.intersects(&rectangle(1.5, 1.5, 2.0, 2.0)));
+        assert!(a.intersects(&rectangle(2.0, 0.0, 2.0, 2.0)));
+        assert!(!a.intersects(&rectangle(3.0, 0.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn intersects_crossing_without_vertices_inside() {
+        // Крест: ни одна вершина не лежит внутри другой фигуры
+        let a = rectangle(0.0, 0.0, 10.0, 1.0);
+        let b = rectangle(0.0, 0.0, 1.0, 10.0);
+
+        assert!(a.intersects(&b));
+    }
+
+    #[test]
+    fn intersects_triangle_and_circle() {
+        let tri = triangle([(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)]);
+
+        assert!(tri.intersects(&circle(3.0, 3.0, 1.5)));
+        assert!(!tri.intersects(&circle(3.0, 3.0, 1.0)));
+        assert!(circle(3.0, 3.0, 1.5).intersects(&tri));
+    }
+
+    #[test]
+    fn intersects_ellipses() {
+        let a = Outline::Ellipse { center: ORIGIN, hradius: 3.0, vradius: 1.0, angle: 0.0 };
+        let b = Outline::Ellipse {
+            center: Point { x: 0.0, y: 3.5 },
+            hradius: 3.0,
+            vradius: 1.0,
+            angle: 90.0,
+        };
+
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&circle(0.0, 2.5, 1.0)));
+        assert!(a.intersects(&circle(0.0, 2.0, 1.0)));
+    }
+
+    #[test]
+    fn contains_shapes() {
+        let big = rectangle(0.0, 0.0, 10.0, 10.0);
+
+        assert!(big.contains(&circle(0.0, 0.0, 5.0)));
+        assert!(!big.contains(&circle(0.0, 0.0, 5.1)));
+        assert!(big.contains(&triangle([(0.0, 0.0), (5.0, 0.0), (0.0, 5.0)])));
+        assert!(!big.contains(&rectangle(4.0, 0.0, 3.0, 1.0)));
+        assert!(circle(0.0, 0.0, 8.0).contains(&big));
+        assert!(!circle(0.0, 0.0, 7.0).contains(&big));
+        assert!(circle(0.0, 0.0, 8.0).contains(&circle(1.0, 1.0, 2.0)));
+        assert!(!circle(0.0, 0.0, 2.0).contains(&circle(1.0, 1.0, 2.0)));
+    }
+
+    #[test]
+    fn contains_concave_polygon() {
+        // "Подкова": прямоугольник между рогами не содержится целиком
+        let horseshoe = Outline::Polygon(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 6.0, y: 0.0 },
+            Point { x: 6.0, y: 6.0 },
+            Point { x: 4.0, y: 6.0 },
+            Point { x: 4.0, y: 2.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 2.0, y: 6.0 },
+            Point { x: 0.0, y: 6.0 },
+        ]);
+
+        assert!(!horseshoe.contains(&rectangle(3.0, 3.0, 5.0, 1.0)));
+        assert!(horseshoe.contains(&rectangle(3.0, 1.0, 5.0, 1.0)));
+    }
+
+    #[test]
+    fn perimeter_correct() {
+        assert_eq!(8.0, rectangle(0.0, 0.0, 3.0, 1.0).perimeter());
+        assert_eq!(12.0, triangle([(0.0, 0.0), (4.0, 0.0), (0.0, 3.0)]).perimeter());
+        assert_relative_eq!(2.0 * PI * 2.0, circle(0.0, 0.0, 2.0).perimeter());
+    }
+
+    #[test]
+    fn perimeter_ellipse_ramanujan() {
+        let ellipse = Outline::Ellipse { center: ORIGIN, hradius: 10.0, vradius: 5.0, angle: 0.0 };
+
+        assert_relative_eq!(48.442_205, ellipse.perimeter(), epsilon = 1e-3);
+    }
+
+    #[test]
+    fn centroid_triangle() {
+        let tri = triangle([(0.0, 0.0), (3.0, 0.0), (0.0, 3.0)]);
+
+        assert_eq!(Point { x: 1.0, y: 1.0 }, tri.centroid());
+    }
+
+    #[test]
+    fn moments_rectangle() {
+        let moments = rectangle(5.0, -2.0, 3.0, 2.0).moments();
+
+        assert_relative_eq!(3.0 * 8.0 / 12.0, moments.ix, epsilon = 1e-4);
+        assert_relative_eq!(2.0 * 27.0 / 12.0, moments.iy, epsilon = 1e-4);
+        assert_relative_eq!(0.0, moments.ixy, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn moments_triangle() {
+        // Прямоугольный треугольник с катетами b=3 и h=6:
+        // Ix = b*h³/36, Iy = h*b³/36, Ixy = -b²h²/72
+        let moments = triangle([(0.0, 0.0), (3.0, 0.0), (0.0, 6.0)]).moments();
+
+        assert_relative_eq!(18.0, moments.ix, epsilon = 1e-4);
+        assert_relative_eq!(4.5, moments.iy, epsilon = 1e-4);
+        assert_relative_eq!(-4.5, moments.ixy, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn moments_rotated_ellipse() {
+        let ellipse = Outline::Ellipse { center: ORIGIN, hradius: 2.0, vradius: 1.0, angle: 90.0 };
+        let moments = ellipse.moments();
+
+        assert_relative_eq!(PI * 8.0 / 4.0, moments.ix, epsilon = 1e-4);
+        assert_relative_eq!(PI * 2.0 / 4.0, moments.iy, epsilon = 1e-4);
+        assert_relative_eq!(0.0, moments.ixy, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn moments_group_matches_whole() {
+        // Два квадрата 1x1 рядом -- то же, что прямоугольник 2x1
+        let group = Outline::Group(vec![rectangle(0.5, 0.5, 1.0, 1.0), rectangle(1.5, 0.5, 1.0, 1.0)]);
+        let whole = rectangle(1.0, 0.5, 2.0, 1.0).moments();
+        let moments = group.moments();
+
+        assert_eq!(Point { x: 1.0, y: 0.5 }, group.centroid());
+        assert_relative_eq!(whole.ix, moments.ix, epsilon = 1e-5);
+        assert_relative_eq!(whole.iy, moments.iy, epsilon = 1e-5);
+        assert_relative_eq!(whole.ixy, moments.ixy, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn approx_eq_ignores_representation() {
+        let square = rectangle(1.0, 1.0, 2.0, 2.0);
+        let turned = Outline::Rectangle { center: Point { x: 1.0, y: 1.0 }, width: 2.0, height: 2.0, angle: 90.0 };
+        let reversed = Outline::Polygon(vec![
+            Point { x: 0.0, y: 2.0 }, Point { x: 2.0, y: 2.0 }, Point { x: 2.0, y: 0.0 }, Point { x: 0.0, y: 0.0 },
+        ]);
+
+        assert!(square.approx_eq(&turned, DEFAULT_EPSILON));
+        assert!(square.approx_eq(&reversed, DEFAULT_EPSILON));
+        assert!(!square.approx_eq(&rectangle(1.0, 1.0, 2.0, 2.01), DEFAULT_EPSILON));
+    }
+
+    #[test]
+    fn approx_eq_ellipses_and_groups() {
+        let ellipse = |hradius, vradius, angle| Outline::Ellipse { center: ORIGIN, hradius, vradius, angle };
+
+        assert!(ellipse(2.0, 1.0, 0.0).approx_eq(&ellipse(1.0, 2.0, 90.0), DEFAULT_EPSILON));
+        assert!(ellipse(1.0, 1.0, 0.0).approx_eq(&ellipse(1.0, 1.0, 37.0), DEFAULT_EPSILON));
+        assert!(!ellipse(2.0, 1.0, 0.0).approx_eq(&ellipse(2.0, 1.0, 90.0), DEFAULT_EPSILON));
+
+        let group = Outline::Group(vec![ellipse(2.0, 1.0, 0.0), rectangle(0.0, 0.0, 1.0, 1.0)]);
+        let swapped = Outline::Group(vec![rectangle(0.0, 0.0, 1.0, 1.0), ellipse(1.0, 2.0, 90.0)]);
+        assert!(group.approx_eq(&swapped, DEFAULT_EPSILON));
+        assert!(!group.approx_eq(&ellipse(2.0, 1.0, 0.0), DEFAULT_EPSILON));
+    }
+
+    /// Тонкий эллипс, касающийся единичного круга малой полуосью,
+    /// с зазором `gap`
+    fn thin_ellipse(gap: Float, angle: Float) -> Outline {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let distance = 1.0 + 0.01 + gap;
+        Outline::Ellipse {
+            center: Point { x: -sin * distance, y: cos * distance },
+            hradius: 10.0,
+            vradius: 0.01,
+            angle,
+        }
+    }
+
+    #[test]
+    fn thin_ellipses_near_tangency() {
+        let circle = Outline::Ellipse { center: ORIGIN, hradius: 1.0, vradius: 1.0, angle: 0.0 };
+
+        for angle in [0.0, 33.0, 101.5] {
+            assert!(circle.intersects(&thin_ellipse(-1e-3, angle)));
+            assert!(thin_ellipse(-1e-3, angle).intersects(&circle));
+            assert!(!circle.intersects(&thin_ellipse(1e-3, angle)));
+            assert!(!thin_ellipse(1e-3, angle).intersects(&circle));
+        }
+    }
+
+    #[test]
+    fn thin_ellipse_contains_near_boundary() {
+        // Эксцентриситет внешнего эллипса около 0.9987
+        let (sin, cos) = (20.0 as Float).to_radians().sin_cos();
+        let outer = Outline::Ellipse { center: ORIGIN, hradius: 10.0, vradius: 0.5, angle: 20.0 };
+        let inner = |shift: Float, hradius: Float, vradius: Float| Outline::Ellipse {
+            center: Point { x: -sin * shift, y: cos * shift },
+            hradius,
+            vradius,
+            angle: 20.0,
+        };
+
+        assert!(outer.contains(&inner(0.0, 9.99, 0.499)));
+        assert!(!outer.contains(&inner(0.0, 10.0, 0.5005)));
+        assert!(outer.contains(&inner(0.09, 5.0, 0.4)));
+        assert!(!outer.contains(&inner(0.11, 5.0, 0.4)));
+    }
+
+    #[test]
+    fn tiny_shapes_are_not_touching() {
+        let tri = triangle([(0.0, 0.0), (1e-6, 0.0), (0.0, 1e-6)]);
+
+        assert!(tri.contains_point(&Point { x: 3e-7, y: 3e-7 }));
+        assert!(tri.contains_point(&Point { x: 1e-6, y: 0.0 }));
+        assert!(!tri.contains_point(&Point { x: 6e-7, y: 6e-7 }));
+        assert!(!tri.contains_point(&Point { x: 5e-6, y: 5e-6 }));
+        assert!(tri.intersects(&triangle([(1e-6, 0.0), (2e-6, 0.0), (1e-6, 1e-6)])));
+        assert!(!tri.intersects(&triangle([(8e-6, 0.0), (9e-6, 0.0), (8e-6, 1e-6)])));
+        // До гипотенузы от (1e-6, 1e-6) примерно 7.07e-7
+        assert!(tri.intersects(&circle(1e-6, 1e-6, 7.1e-7)));
+        assert!(!tri.intersects(&circle(1e-6, 1e-6, 7e-7)));
+
+        let circle_at = |x: Float| circle(x, 0.0, 1e-6);
+        assert!(circle_at(0.0).intersects(&circle_at(2e-6)));
+        assert!(!circle_at(0.0).intersects(&circle_at(2.01e-6)));
+        assert!(rectangle(0.0, 0.0, 2e-6, 2e-6).contains(&circle_at(0.0)));
+        assert!(!rectangle(0.0, 0.0, 2e-6, 2e-6).contains(&circle_at(2e-8)));
+    }
+
+    #[test]
+    fn large_shapes_touch_despite_rounding() {
+        let (x, y): (Float, Float) = (1e6, 1e6);
+
+        // В f32 центры округляются до 1/16 и расходятся на 0.03
+        assert!(circle(x + 0.33, y, 0.61).intersects(&circle(x + 1.55, y, 0.61)));
+        assert!(!circle(x, y, 0.6).intersects(&circle(x + 4.2, y, 0.6)));
+        assert!(rectangle(x, y, 2.0, 2.0).intersects(&rectangle(x + 2.0, y + 1.0, 2.0, 2.0)));
+        assert!(!rectangle(x, y, 2.0, 2.0).intersects(&rectangle(x + 6.0, y, 2.0, 2.0)));
+        assert!(rectangle(x, y, 2.0, 2.0).contains(&circle(x, y, 1.0)));
+        assert!(!rectangle(x, y, 2.0, 2.0).contains(&circle(x + 4.0, y, 1.0)));
+
+        let tri = triangle([(x, y), (x + 3.0, y), (x, y + 3.0)]);
+        assert!(tri.contains_point(&Point { x: x + 1.1, y: y + 1.9 }));
+        assert!(!tri.contains_point(&Point { x: x + 3.0, y: y + 3.0 }));
+        assert!(tri.intersects(&circle(x + 1.5, y + 1.5, 0.1)));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::base_types::{Float, FLOAT_NAME};
+use crate::errors::{Diagnostic, ErrorKind};
+use crate::validate::ValidationError;
+
+///
+/// # Параметры строки команды
+///
+/// Выдаёт параметры по одному и строит диагностику с номером
+/// параметра и его позицией в строке
+///
+pub(crate) struct Params<'a> {
+    keyword: &'static str,
+    source: &'a str,
+    tokens: Vec<&'a str>,
+    position: usize,
+    need: usize,
+}
+
+impl<'a> Params<'a> {
+    /// `need` -- сколько параметров ожидает команда, для сообщений об ошибках
+    pub(crate) fn new(keyword: &'static str, source: &'a str, need: usize) -> Params<'a> {
+        Params {
+            keyword,
+            source,
+            tokens: source.split_whitespace().collect(),
+            position: 0,
+            need,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Позиция параметра в строке, с единицы
+    fn column(&self, token: usize) -> usize {
+        let offset = match self.tokens.get(token) {
+            Some(token) => token.as_ptr() as usize - self.source.as_ptr() as usize,
+            None => self.source.len(),
+        };
+        self.source[..offset].chars().count() + 1
+    }
+
+    /// # Ошибка, относящаяся ко всей строке
+    pub(crate) fn error(&self, kind: ErrorKind, cause: &str) -> Diagnostic {
+        Diagnostic::new(self.keyword, self.source, kind, cause.to_string())
+    }
+
+    /// # Параметры разобраны, но задают некорректную фигуру
+    pub(crate) fn invalid(&self, error: ValidationError) -> Diagnostic {
+        self.error(ErrorKind::InvalidGeometry, &error.to_string())
+    }
+
+    /// # Ошибка, относящаяся к параметру с номером `token`
+    pub(crate) fn token_error(&self, token: usize, kind: ErrorKind, cause: String) -> Diagnostic {
+        Diagnostic::new(self.keyword, self.source, kind, cause)
+            .at_token(token, self.column(token))
+    }
+
+    fn next_token(&mut self) -> Result<(usize, &'a str), Diagnostic> {
+        let position = self.position;
+        let Some(token) = self.tokens.get(position) else {
+            let cause = if position == 0 {
+                format!("No parameters provided (need {})", self.need)
+            } else {
+                format!("Not enough parameters (need {})", self.need)
+            };
+            return Err(self.token_error(position, ErrorKind::MissingParameter, cause));
+        };
+        self.position += 1;
+        Ok((position, token))
+    }
+
+    /// # Необязательное ключевое слово
+    ///
+    /// Если следующий параметр -- одно из `keywords`, забирает его
+    /// и возвращает, иначе ничего не меняет
+    pub(crate) fn next_keyword(&mut self, keywords: &[&'static str]) -> Option<&'static str> {
+        let token = self.tokens.get(self.position)?;
+        let keyword = keywords.iter().find(|&&keyword| keyword == *token)?;
+        self.position += 1;
+        Some(keyword)
+    }
+
+    /// # Проверить, что все параметры разобраны
+    ///
+    /// Лишние параметры -- ошибка, указывающая на первый из них
+    pub(crate) fn finish(&self) -> Result<(), Diagnostic> {
+        if self.position >= self.tokens.len() {
+            return Ok(());
+        }
+        Err(self.token_error(
+            self.position,
+            ErrorKind::WrongParameterCount,
+            format!("Too many parameters (need {})", self.need),
+        ))
+    }
+
+    /// # Следующий параметр как число с плавающей точкой
+    pub(crate) fn next_float(&mut self) -> Result<Float, Diagnostic> {
+        let (position, token) = self.next_token()?;
+        token.parse::<Float>().map_err(
+            |_| self.token_error(
+                position,
+                ErrorKind::InvalidNumber,
+                format!("Cannot parse `{}', expected {}", token, FLOAT_NAME),
+            ))
+    }
+
+    /// # Следующий параметр как номер фигуры
+    pub(crate) fn next_index(&mut self) -> Result<usize, Diagnostic> {
+        let (position, token) = self.next_token()?;
+        token.parse::<usize>().map_err(
+            |_| self.token_error(
+                position,
+                ErrorKind::InvalidIndex,
+                format!("Cannot parse `{}', expected index", token),
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_float_reports_column() {
+        let mut params = Params::new("TRIANGLE", "2  1 a 3", 6);
+
+        assert_eq!(Ok(2.0), params.next_float());
+        assert_eq!(Ok(1.0), params.next_float());
+        let err = params.next_float().unwrap_err();
+
+        assert_eq!(ErrorKind::InvalidNumber, err.kind);
+        assert_eq!(Some(2), err.token);
+        assert_eq!(6, err.column);
+    }
+
+    #[test]
+    fn missing_parameter() {
+        let mut params = Params::new("SCALE", "1.0", 3);
+
+        assert!(params.next_float().is_ok());
+        let err = params.next_float().unwrap_err();
+
+        assert_eq!(ErrorKind::MissingParameter, err.kind);
+        assert_eq!(Some(1), err.token);
+        assert_eq!("Not enough parameters (need 3)", &*err.cause);
+    }
+
+    #[test]
+    fn finish_reports_extra_parameter() {
+        let mut params = Params::new("MOVE", "1 2 3", 2);
+
+        params.next_float().unwrap();
+        params.next_float().unwrap();
+        let err = params.finish().unwrap_err();
+
+        assert_eq!(ErrorKind::WrongParameterCount, err.kind);
+        assert_eq!(Some(2), err.token);
+        assert_eq!(5, err.column);
+        assert_eq!("Too many parameters (need 2)", &*err.cause);
+    }
+}
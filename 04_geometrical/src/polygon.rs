@@ -0,0 +1,347 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{Diagnostic, ErrorKind};
+use crate::parse::Params;
+use crate::shape::Shape;
+use crate::base_types::{Point, Rect, Float, ORIGIN, EPS, collinear, segments_intersect};
+use crate::outline::{Outline, polygon_centroid, polygon_signed_area};
+use crate::affine::Affine2;
+use crate::validate::{Validate, ValidationError, check_numbers};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Polygon {
+    pub vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Polygon {
+        Polygon { vertices }
+    }
+
+    /// # Ориентированная площадь (формула шнурования)
+    ///
+    /// Положительна при обходе вершин против часовой стрелки
+    fn signed_area(&self) -> Float {
+        polygon_signed_area(&self.vertices)
+    }
+
+    fn centroid(&self) -> Point {
+        polygon_centroid(&self.vertices)
+    }
+
+    fn scale_points(&mut self, factor: Float) {
+        for vertex in &mut self.vertices {
+            *vertex *= factor;
+        }
+    }
+}
+
+impl Shape for Polygon {
+    fn get_center(&self) -> Point {
+        self.centroid()
+    }
+
+    fn get_area(&self) -> Float {
+        self.signed_area().abs()
+    }
+
+    fn get_frame_rect(&self) -> Rect {
+        Rect::bounding(&self.vertices)
+    }
+
+    fn move_at(&mut self, target: &Point) {
+        let delta = *target - self.centroid();
+        self.move_by(delta.x, delta.y);
+    }
+
+    fn move_by(&mut self, x: Float, y: Float) {
+        for vertex in &mut self.vertices {
+            vertex.x += x;
+            vertex.y += y;
+        }
+    }
+
+    fn scale(&mut self, factor: Float) {
+        let center = self.centroid();
+
+        self.move_at(&ORIGIN);
+
+        self.scale_points(factor);
+
+        self.move_at(&center);
+    }
+
+    fn get_outline(&self) -> Outline {
+        Outline::Polygon(self.vertices.clone())
+    }
+
+    fn rotate(&mut self, angle: Float) {
+        let center = self.centroid();
+        for vertex in &mut self.vertices {
+            *vertex = vertex.rotated(&center, angle);
+        }
+    }
+
+    fn transform(&mut self, affine: &Affine2) {
+        for vertex in &mut self.vertices {
+            *vertex = affine.apply(vertex);
+        }
+    }
+}
+
+impl Validate for Polygon {
+    /// # Проверка корректности многоугольника
+    ///
+    /// Многоугольник некорректен, если у него меньше трёх вершин,
+    /// есть совпадающие вершины, нулевая площадь или его стороны
+    /// пересекают друг друга
+    fn validate(&self) -> Result<(), ValidationError> {
+        let n = self.vertices.len();
+        if n < 3 {
+            return Err(ValidationError::TooFewVertices);
+        }
+        check_numbers(&self.vertices, &[])?;
+        for i in 0..n {
+            for j in (i + 1)..n {
+                if self.vertices[i] == self.vertices[j] {
+                    return Err(ValidationError::DuplicateVertices);
+                }
+            }
+        }
+        let perimeter_square: Float = (0..n)
+            .map(|i| {
+                let (a, b) = (&self.vertices[i], &self.vertices[(i + 1) % n]);
+                (b.x - a.x).powi(2) + (b.y - a.y).powi(2)
+            })
+            .sum();
+        if self.signed_area().abs() <= EPS * perimeter_square {
+            return Err(ValidationError::ZeroArea);
+        }
+
+        for i in 0..n {
+            let a = &self.vertices[i];
+            let b = &self.vertices[(i + 1) % n];
+            for j in (i + 1)..n {
+                let c = &self.vertices[j];
+                let d = &self.vertices[(j + 1) % n];
+                let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+                if adjacent {
+                    // Соседние стороны имеют общую вершину, поэтому пересекаются
+                    // всегда. Некорректно только наложение: сторона
+                    // разворачивается назад вдоль предыдущей
+                    let (shared, p, q) = if j == i + 1 { (b, a, d) } else { (a, b, c) };
+                    let dot = (p.x - shared.x) * (q.x - shared.x)
+                        + (p.y - shared.y) * (q.y - shared.y);
+                    if collinear(shared, p, q) && dot > 0.0 {
+                        return Err(ValidationError::SelfIntersection);
+                    }
+                } else if segments_intersect(a, b, c, d) {
+                    return Err(ValidationError::SelfIntersection);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Polygon {
+    type Err = Diagnostic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Params::new("POLYGON", s, 6);
+
+        if params.len() == 0 {
+            return Err(params.error(
+                ErrorKind::MissingParameter,
+                "No coords provided (need at least 6)",
+            ))
+        }
+        if !params.len().is_multiple_of(2) {
+            return Err(params.error(
+                ErrorKind::WrongParameterCount,
+                "Odd number of coords (need pairs of x y)",
+            ))
+        }
+        if params.len() < 6 {
+            return Err(params.error(
+                ErrorKind::MissingParameter,
+                "Not enough coords (need at least 6)",
+            ))
+        }
+
+        let mut vertices = Vec::with_capacity(params.len() / 2);
+        for _ in 0..params.len() / 2 {
+            let x = params.next_float()?;
+            let y = params.next_float()?;
+            vertices.push(Point { x, y });
+        }
+
+        let polygon = Polygon::new(vertices);
+        polygon.validate().map_err(|err| params.invalid(err))?;
+
+        Ok(polygon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Polygon {
+        Polygon::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 0.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 0.0, y: 2.0 },
+        ])
+    }
+
+    #[test]
+    fn parse_correct_polygon() {
+        let polygon_str = "0.0 0.0 2.0 0.0 2.0 2.0 0.0 2.0";
+
+        let result: Polygon = polygon_str.parse().unwrap();
+
+        assert_eq!(square(), result);
+    }
+
+    #[test]
+    fn parse_odd_coords() {
+        let result = "0.0 0.0 2.0 0.0 2.0".parse::<Polygon>();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_not_enough_vertices() {
+        let result = "0.0 0.0 2.0 0.0".parse::<Polygon>();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_self_intersecting() {
+        // "Бантик": стороны 1-2 и 3-4 пересекаются
+        let result = "0.0 0.0 2.0 2.0 2.0 0.0 0.0 2.0".parse::<Polygon>();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_collinear() {
+        let result = "0.0 0.0 1.0 1.0 2.0 2.0".parse::<Polygon>();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_backtracking_edge() {
+        let result = "0.0 0.0 4.0 0.0 2.0 0.0 2.0 2.0".parse::<Polygon>();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_concave() {
+        let result = "0.0 0.0 4.0 0.0 4.0 4.0 2.0 1.0 0.0 4.0".parse::<Polygon>();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn get_area_correct() {
+        assert_eq!(4.0, square().get_area());
+    }
+
+    #[test]
+    fn get_area_clockwise() {
+        let polygon = Polygon::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 0.0, y: 2.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 2.0, y: 0.0 },
+        ]);
+
+        assert_eq!(4.0, polygon.get_area());
+    }
+
+    #[test]
+    fn center_correct() {
+        let polygon = Polygon::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 0.0 },
+            Point { x: 4.0, y: 2.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 2.0, y: 4.0 },
+            Point { x: 0.0, y: 4.0 },
+        ]);
+
+        // Г-образная фигура: квадрат 4x4 без угла 2x2
+        let center = polygon.get_center();
+        assert_relative_eq!(5.0 / 3.0, center.x);
+        assert_relative_eq!(5.0 / 3.0, center.y);
+    }
+
+    #[test]
+    fn get_frame_rect_correct() {
+        let rect = Rect { pos: Point { x: 1.0, y: 1.0 }, width: 2.0, height: 2.0 };
+
+        assert_eq!(rect, square().get_frame_rect());
+    }
+
+    #[test]
+    fn move_at_correct() {
+        let mut polygon = square();
+
+        polygon.move_at(&Point { x: 5.0, y: 5.0 });
+
+        assert_eq!(Point { x: 4.0, y: 4.0 }, polygon.vertices[0]);
+        assert_eq!(Point { x: 6.0, y: 6.0 }, polygon.vertices[2]);
+    }
+
+    #[test]
+    fn move_by_correct() {
+        let mut polygon = square();
+
+        polygon.move_by(1.0, -1.0);
+
+        assert_eq!(Point { x: 1.0, y: -1.0 }, polygon.vertices[0]);
+        assert_eq!(Point { x: 3.0, y: 1.0 }, polygon.vertices[2]);
+    }
+
+    #[test]
+    fn scale_correct() {
+        let mut polygon = square();
+
+        polygon.scale(2.0);
+
+        assert_eq!(Point { x: -1.0, y: -1.0 }, polygon.vertices[0]);
+        assert_eq!(Point { x: 3.0, y: 3.0 }, polygon.vertices[2]);
+        assert_eq!(16.0, polygon.get_area());
+    }
+
+    #[test]
+    fn rotate_correct() {
+        let mut polygon = square();
+
+        polygon.rotate(90.0);
+
+        assert_relative_eq!(2.0, polygon.vertices[0].x, epsilon = 1e-6);
+        assert_relative_eq!(0.0, polygon.vertices[0].y, epsilon = 1e-6);
+        assert_relative_eq!(4.0, polygon.get_area(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn transform_reflection() {
+        let mut polygon = square();
+
+        polygon.transform(&Affine2::scaling(-1.0, 3.0));
+
+        assert_eq!(Point { x: 0.0, y: 0.0 }, polygon.vertices[0]);
+        assert_eq!(Point { x: -2.0, y: 6.0 }, polygon.vertices[2]);
+        assert_eq!(12.0, polygon.get_area());
+        assert_eq!(Ok(()), polygon.validate());
+    }
+}
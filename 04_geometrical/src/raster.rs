@@ -0,0 +1,323 @@
+use std::io;
+use std::io::Write;
+
+use crate::base_types::{Float, Point, Rect};
+use crate::shape::Shape;
+
+/// Подвыборок на пиксель по каждой оси для сглаживания
+const SAMPLES: usize = 4;
+/// Поля вокруг фигур в долях размера рисунка
+const MARGIN: Float = 0.05;
+
+///
+/// # Цвет пикселя
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color { r: 255, g: 255, b: 255 };
+    pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /// Цвет, смешанный с `other` в доле `alpha` от 0 до 1
+    fn blend(self, other: Color, alpha: Float) -> Color {
+        let mix = |from: u8, to: u8| (from as Float + (to as Float - from as Float) * alpha).round() as u8;
+        Color {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+        }
+    }
+}
+
+/// Цвета фигур по умолчанию, те же, что у слоёв SVG
+pub const PALETTE: [Color; 4] = [
+    Color::new(0x1f, 0x77, 0xb4),
+    Color::new(0xd6, 0x27, 0x28),
+    Color::new(0x2c, 0xa0, 0x2c),
+    Color::new(0x94, 0x67, 0xbd),
+];
+
+///
+/// # Соответствие координат сцены и пикселей
+///
+/// Пиксели отсчитываются от левого верхнего угла рисунка, ось y
+/// направлена вниз, масштаб по осям одинаковый
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Точка сцены в левом верхнем углу рисунка
+    pub origin: Point,
+    /// Пикселей на единицу длины
+    pub scale: Float,
+}
+
+impl Viewport {
+    /// # Вписать прямоугольники `frames` в рисунок `width` на `height`
+    ///
+    /// Объединение прямоугольников оказывается в центре рисунка
+    /// с небольшими полями
+    pub fn fit(frames: &[Rect], width: usize, height: usize) -> Viewport {
+        let corners: Vec<Point> = frames
+            .iter()
+            .flat_map(|frame| [frame.left_bot(), frame.right_top()])
+            .collect();
+        let bounds = if corners.is_empty() {
+            Rect { pos: Point { x: 0.0, y: 0.0 }, width: 2.0, height: 2.0 }
+        } else {
+            Rect::bounding(&corners)
+        };
+
+        let usable = 1.0 - 2.0 * MARGIN;
+        let mut scale = (width as Float * usable / bounds.width)
+            .min(height as Float * usable / bounds.height);
+        // Сцена из одной точки
+        if !scale.is_finite() || scale <= 0.0 {
+            scale = 1.0;
+        }
+
+        Viewport {
+            origin: Point {
+                x: bounds.pos.x - width as Float / 2.0 / scale,
+                y: bounds.pos.y + height as Float / 2.0 / scale,
+            },
+            scale,
+        }
+    }
+
+    /// # Положение точки сцены на рисунке, в пикселях
+    pub fn to_pixels(&self, point: &Point) -> (Float, Float) {
+        ((point.x - self.origin.x) * self.scale, (self.origin.y - point.y) * self.scale)
+    }
+
+    /// # Точка сцены в положении `(x, y)` на рисунке
+    pub fn to_scene(&self, x: Float, y: Float) -> Point {
+        Point { x: self.origin.x + x / self.scale, y: self.origin.y - y / self.scale }
+    }
+}
+
+///
+/// # Растровый рисунок
+///
+/// Фигуры закрашиваются со сглаживанием краёв, каждая следующая
+/// поверх предыдущих. Сохраняется в PPM или PNG
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Color) -> Image {
+        Image { width, height, pixels: vec![background; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// # Цвет пикселя в столбце `x` и строке `y`
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// # Нарисовать фигуры на белом фоне
+    ///
+    /// Фигура `i` получает цвет `colors[i % colors.len()]`, при пустом
+    /// `colors` -- цвет из `PALETTE`. Область рисования подбирается по
+    /// ограничивающим прямоугольникам фигур
+    pub fn render(shapes: &[Box<dyn Shape>], colors: &[Color], width: usize, height: usize) -> Image {
+        let colors = if colors.is_empty() { &PALETTE[..] } else { colors };
+        let frames: Vec<Rect> = shapes.iter().map(|shape| shape.get_frame_rect()).collect();
+        let viewport = Viewport::fit(&frames, width, height);
+        let mut image = Image::new(width, height, Color::WHITE);
+        for (i, shape) in shapes.iter().enumerate() {
+            image.fill(&viewport, shape.as_ref(), colors[i % colors.len()]);
+        }
+        image
+    }
+
+    /// # Закрасить фигуру
+    ///
+    /// Доля пикселя, покрытая фигурой, оценивается по `SAMPLES` на
+    /// `SAMPLES` точкам внутри пикселя. Эллипсы заменяются
+    /// многоугольниками с отклонением меньше четверти пикселя
+    pub fn fill(&mut self, viewport: &Viewport, shape: &dyn Shape, color: Color) {
+        let polygons: Vec<Vec<(Float, Float)>> = shape
+            .get_outline()
+            .polygons(0.25 / viewport.scale)
+            .iter()
+            .map(|polygon| polygon.iter().map(|vertex| viewport.to_pixels(vertex)).collect())
+            .collect();
+
+        let (mut min, mut max) = ((Float::MAX, Float::MAX), (Float::MIN, Float::MIN));
+        for &(x, y) in polygons.iter().flatten() {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let left = min.0.floor().clamp(0.0, self.width as Float) as usize;
+        let right = max.0.ceil().clamp(0.0, self.width as Float) as usize;
+        let top = min.1.floor().clamp(0.0, self.height as Float) as usize;
+        let bottom = max.1.ceil().clamp(0.0, self.height as Float) as usize;
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let columns = right - left;
+        let mut coverage = vec![0usize; columns];
+        // Подвыборки строки, попавшие в фигуру: части группы могут
+        // перекрываться, а учесть подвыборку нужно один раз
+        let mut covered = vec![false; columns * SAMPLES];
+        let mut crossings: Vec<Float> = Vec::new();
+        let step = 1.0 / SAMPLES as Float;
+        let samples = covered.len();
+
+        for row in top..bottom {
+            coverage.fill(0);
+            for sub in 0..SAMPLES {
+                let y = row as Float + (sub as Float + 0.5) * step;
+                covered.fill(false);
+                for polygon in &polygons {
+                    crossings.clear();
+                    for (k, &(ax, ay)) in polygon.iter().enumerate() {
+                        let (bx, by) = polygon[(k + 1) % polygon.len()];
+                        if (ay <= y) != (by <= y) {
+                            crossings.push(ax + (y - ay) / (by - ay) * (bx - ax));
+                        }
+                    }
+                    crossings.sort_by(|a, b| a.total_cmp(b));
+                    // Подвыборка s лежит в x = left + (s + 0.5) * step
+                    let sample = |x: Float| {
+                        (((x - left as Float) * SAMPLES as Float - 0.5).ceil().max(0.0) as usize)
+                            .min(samples)
+                    };
+                    for span in crossings.chunks_exact(2) {
+                        let (first, last) = (sample(span[0]), sample(span[1]));
+                        covered[first..last.max(first)].fill(true);
+                    }
+                }
+                for (s, _) in covered.iter().enumerate().filter(|(_, covered)| **covered) {
+                    coverage[s / SAMPLES] += 1;
+                }
+            }
+
+            for (column, &count) in coverage.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+                let pixel = &mut self.pixels[row * self.width + left + column];
+                *pixel = pixel.blend(color, count as Float / (SAMPLES * SAMPLES) as Float);
+            }
+        }
+    }
+
+    fn rgb_bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|color| [color.r, color.g, color.b]).collect()
+    }
+
+    /// # Сохранить в формате PPM (P6)
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.rgb_bytes())
+    }
+
+    /// # Сохранить в формате PNG
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&self.rgb_bytes()).map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rectangle::Rectangle;
+    use crate::ellipse::Ellipse;
+
+    #[test]
+    fn viewport_fits_frames() {
+        let frames = [Rect { pos: Point { x: 5.0, y: 5.0 }, width: 10.0, height: 2.0 }];
+
+        let viewport = Viewport::fit(&frames, 100, 50);
+        let (x, y) = viewport.to_pixels(&Point { x: 5.0, y: 5.0 });
+        let corner = viewport.to_scene(0.0, 0.0);
+
+        assert_relative_eq!(9.0, viewport.scale, epsilon = 1e-5);
+        assert_relative_eq!(50.0, x, epsilon = 1e-4);
+        assert_relative_eq!(25.0, y, epsilon = 1e-4);
+        assert_relative_eq!(viewport.origin.x, corner.x, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn fill_antialiases_edges() {
+        let mut image = Image::new(4, 4, Color::WHITE);
+        let viewport = Viewport { origin: Point { x: 0.0, y: 4.0 }, scale: 1.0 };
+        // Квадрат покрывает пиксель (1, 1) целиком и половину пикселя (2, 1)
+        let square: Rectangle = "1 1.5 2.5 3".parse().unwrap();
+
+        image.fill(&viewport, &square, Color::BLACK);
+
+        assert_eq!(Color::BLACK, image.pixel(1, 1));
+        assert_eq!(Color::new(128, 128, 128), image.pixel(2, 1));
+        assert_eq!(Color::WHITE, image.pixel(3, 1));
+        assert_eq!(Color::WHITE, image.pixel(0, 0));
+    }
+
+    #[test]
+    fn render_with_colors() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new("0 0 4 4".parse::<Rectangle>().unwrap()),
+            Box::new("2 2 1 1".parse::<Ellipse>().unwrap()),
+        ];
+
+        let image = Image::render(&shapes, &PALETTE, 40, 40);
+
+        assert_eq!(PALETTE[1], image.pixel(20, 20));
+        assert_eq!(PALETTE[0], image.pixel(5, 5));
+        assert_eq!(Color::WHITE, image.pixel(0, 0));
+    }
+
+    #[test]
+    fn render_without_colors_uses_palette() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new("0 0 4 4".parse::<Rectangle>().unwrap()),
+            Box::new("2 2 1 1".parse::<Ellipse>().unwrap()),
+        ];
+
+        let image = Image::render(&shapes, &[], 40, 40);
+
+        assert_eq!(PALETTE[1], image.pixel(20, 20));
+        assert_eq!(PALETTE[0], image.pixel(5, 5));
+    }
+
+    #[test]
+    fn write_formats() {
+        let image = Image::new(2, 1, Color::new(1, 2, 3));
+        let mut ppm = Vec::new();
+        let mut png = Vec::new();
+
+        image.write_ppm(&mut ppm).unwrap();
+        image.write_png(&mut png).unwrap();
+
+        assert_eq!(b"P6\n2 1\n255\n\x01\x02\x03\x01\x02\x03".to_vec(), ppm);
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+    }
+}
@@ -1,13 +1,32 @@
 use std::str::FromStr;
 
-use crate::errors::ParseRectangleError;
-use crate::shape::Shape;
-use crate::base_types::{Point, Rect, ORIGIN};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+use crate::errors::Diagnostic;
+use crate::parse::Params;
+use crate::shape::Shape;
+use crate::base_types::{Point, Rect, Float, ORIGIN};
+use crate::outline::Outline;
+use crate::affine::Affine2;
+use crate::validate::{Validate, ValidationError, check_numbers};
+
+///
+/// # Прямоугольник
+///
+/// `left_bot` и `right_top` задают углы прямоугольника до поворота,
+/// `angle` -- поворот вокруг центра в градусах против часовой стрелки.
+/// `shear` -- скос до поворота: верхняя сторона сдвинута относительно
+/// нижней на `shear * height` вдоль оси x. Скос появляется только после
+/// аффинных преобразований и делает прямоугольник параллелограммом
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rectangle {
     pub left_bot: Point,
     pub right_top: Point,
+    #[serde(default)]
+    pub angle: Float,
+    #[serde(default)]
+    pub shear: Float,
 }
 
 impl Rectangle {
@@ -15,17 +34,19 @@ impl Rectangle {
         Rectangle {
             left_bot,
             right_top,
+            angle: 0.0,
+            shear: 0.0,
         }
     }
 
-    fn width(&self) -> f32 {
+    fn width(&self) -> Float {
         let first = &self.left_bot;
         let second = &self.right_top;
 
         second.x - first.x
     }
 
-    fn height(&self) -> f32 {
+    fn height(&self) -> Float {
         let first = &self.left_bot;
         let second = &self.right_top;
 
@@ -47,10 +68,54 @@ impl Rectangle {
         }
     }
 
-    fn scale_points(&mut self, scale: f32) {
+    /// # Углы с учётом скоса и поворота
+    ///
+    /// В порядке обхода против часовой стрелки, начиная с левого нижнего
+    fn corners(&self) -> [Point; 4] {
+        let center = self.center();
+        let skew = self.shear * self.height() / 2.0;
+        [
+            Point { x: self.left_bot.x - skew, y: self.left_bot.y },
+            Point { x: self.right_top.x - skew, y: self.left_bot.y },
+            Point { x: self.right_top.x + skew, y: self.right_top.y },
+            Point { x: self.left_bot.x + skew, y: self.right_top.y },
+        ].map(|corner| corner.rotated(&center, self.angle))
+    }
+
+    fn scale_points(&mut self, scale: Float) {
         self.left_bot *= scale;
         self.right_top *= scale;
     }
+
+    /// # Каноническая запись прямоугольника
+    ///
+    /// Углы упорядочиваются, поворот приводится к `[0, 180)`, а без
+    /// скоса -- к `[0, 90)` с заменой ширины на высоту. Центр, площадь
+    /// и контур при этом не меняются
+    pub fn normalized(&self) -> Rectangle {
+        let left_bot = Point {
+            x: self.left_bot.x.min(self.right_top.x),
+            y: self.left_bot.y.min(self.right_top.y),
+        };
+        let right_top = Point {
+            x: self.left_bot.x.max(self.right_top.x),
+            y: self.left_bot.y.max(self.right_top.y),
+        };
+        // Параллелограмм симметричен относительно центра
+        let mut angle = self.angle.rem_euclid(180.0);
+        if angle >= 180.0 {
+            angle = 0.0;
+        }
+        let mut rectangle = Rectangle { left_bot, right_top, angle, shear: self.shear };
+        if self.shear == 0.0 && angle >= 90.0 {
+            let center = rectangle.center();
+            let (half_width, half_height) = (rectangle.height() / 2.0, rectangle.width() / 2.0);
+            rectangle.left_bot = Point { x: center.x - half_width, y: center.y - half_height };
+            rectangle.right_top = Point { x: center.x + half_width, y: center.y + half_height };
+            rectangle.angle = angle - 90.0;
+        }
+        rectangle
+    }
 }
 
 impl Shape for Rectangle {
@@ -59,16 +124,12 @@ impl Shape for Rectangle {
         self.center()
     }
 
-    fn get_area(&self) -> f32 { 
+    fn get_area(&self) -> Float { 
         self.width() * self.height()
     }
 
     fn get_frame_rect(&self) -> Rect { 
-        Rect {
-            width: self.width(),
-            height: self.height(),
-            pos: self.center(),
-        }
+        Rect::bounding(&self.corners())
     }
 
     /// # Передвижение в конкретную точку
@@ -93,8 +154,8 @@ impl Shape for Rectangle {
             y: target.y + height/2.0,
         };
 
-        self.left_bot = new_bot.clone();
-        self.right_top = new_top.clone();
+        self.left_bot = new_bot;
+        self.right_top = new_top;
     }
 
     /// # Передвижение по смещению
@@ -105,7 +166,7 @@ impl Shape for Rectangle {
     ///
     /// (см. [move_at](#method.move_at))
     ///
-    fn move_by(&mut self, x: f32, y: f32) {
+    fn move_by(&mut self, x: Float, y: Float) {
         self.left_bot.x += x;
         self.right_top.x += x;
 
@@ -113,92 +174,131 @@ impl Shape for Rectangle {
         self.right_top.y += y;
     }
 
-    fn scale(&mut self, factor: f32) {
+    /// # Масштабирование относительно центра
+    ///
+    /// Отрицательный коэффициент -- ещё и отражение относительно
+    /// центра, которое прямоугольник не меняет, поэтому углы
+    /// упорядочиваются заново
+    fn scale(&mut self, factor: Float) {
         let center = self.center();
         
         self.move_at(&ORIGIN);
 
         self.scale_points(factor);
+        let (first, second) = (self.left_bot, self.right_top);
+        self.left_bot = Point { x: first.x.min(second.x), y: first.y.min(second.y) };
+        self.right_top = Point { x: first.x.max(second.x), y: first.y.max(second.y) };
 
         self.move_at(&center);
     }
+
+    /// # Неравномерное масштабирование
+    ///
+    /// Без поворота и скоса меняются только ширина и высота, иначе
+    /// прямоугольник в общем случае становится параллелограммом
+    fn scale_xy(&mut self, sx: Float, sy: Float) {
+        let center = self.center();
+        if self.angle != 0.0 || self.shear != 0.0 || sx <= 0.0 || sy <= 0.0 {
+            self.transform(&Affine2::scaling(sx, sy).about(&center));
+            return;
+        }
+        let half_width = self.width() * sx / 2.0;
+        let half_height = self.height() * sy / 2.0;
+        self.left_bot = Point { x: center.x - half_width, y: center.y - half_height };
+        self.right_top = Point { x: center.x + half_width, y: center.y + half_height };
+    }
+
+    fn get_outline(&self) -> Outline {
+        if self.shear != 0.0 {
+            return Outline::Polygon(self.corners().to_vec());
+        }
+        Outline::Rectangle {
+            center: self.center(),
+            width: self.width(),
+            height: self.height(),
+            angle: self.angle,
+        }
+    }
+
+    fn rotate(&mut self, angle: Float) {
+        self.angle = (self.angle + angle) % 360.0;
+    }
+
+    /// # Аффинное преобразование
+    ///
+    /// Образы сторон раскладываются заново на поворот, ширину, высоту
+    /// и скос (QR-разложение). Отражение меняет только порядок обхода
+    /// углов, поэтому высота берётся по модулю
+    fn transform(&mut self, affine: &Affine2) {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let (width, height) = (self.width(), self.height());
+        let center = affine.apply(&self.center());
+
+        let bottom = affine.apply_vector(&Point {
+            x: width * cos,
+            y: width * sin,
+        });
+        let side = affine.apply_vector(&Point {
+            x: height * (self.shear * cos - sin),
+            y: height * (self.shear * sin + cos),
+        });
+
+        let angle = bottom.y.atan2(bottom.x);
+        let (sin, cos) = angle.sin_cos();
+        let width = bottom.x.hypot(bottom.y);
+        let along = side.x * cos + side.y * sin;
+        let across = side.y * cos - side.x * sin;
+        let height = across.abs();
+
+        self.angle = angle.to_degrees();
+        self.shear = if height > 0.0 { along * across.signum() / height } else { 0.0 };
+        self.left_bot = Point { x: center.x - width / 2.0, y: center.y - height / 2.0 };
+        self.right_top = Point { x: center.x + width / 2.0, y: center.y + height / 2.0 };
+    }
+}
+
+impl Validate for Rectangle {
+    /// # Проверка корректности прямоугольника
+    ///
+    /// Правый верхний угол должен лежать правее и выше левого нижнего,
+    /// ширина и высота -- больше нуля
+    fn validate(&self) -> Result<(), ValidationError> {
+        check_numbers(&[self.left_bot, self.right_top], &[self.angle, self.shear])?;
+        if self.right_top.x < self.left_bot.x || self.right_top.y < self.left_bot.y {
+            return Err(ValidationError::InvertedCorners);
+        }
+        if self.width() == 0.0 || self.height() == 0.0 {
+            return Err(ValidationError::ZeroArea);
+        }
+        Ok(())
+    }
 }
 
 impl FromStr for Rectangle {
-    type Err = ParseRectangleError;
+    type Err = Diagnostic;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tokens = s
-            .split_whitespace();
-
-        let x1 = tokens.next().ok_or(
-                ParseRectangleError {
-                    place: s.to_string(), 
-                    cause: "No coords provided (need 4)".to_string(),
-                })?;
-
-        let y1 = tokens.next().ok_or(
-                ParseRectangleError {
-                    place: s.to_string(), 
-                    cause: "Not enought coords (need 4)".to_string(),
-                })?;
-
-        let x2 = tokens.next().ok_or(
-                ParseRectangleError {
-                    place: s.to_string(), 
-                    cause: "Not enought coords (need 4)".to_string(),
-                })?;
-
-        let y2 = tokens.next().ok_or(
-                ParseRectangleError {
-                    place: s.to_string(), 
-                    cause: "Not enought coords (need 4)".to_string(),
-                })?;
-
-        let x = x1.parse::<f32>().map_err(
-            |_| ParseRectangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", x1).to_string(),
-            })?;
-        let y = y1.parse::<f32>().map_err(
-            |_| ParseRectangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", y1).to_string(),
-            })?;
+        let mut params = Params::new("RECTANGLE", s, 4);
+
+        let x = params.next_float()?;
+        let y = params.next_float()?;
         let left_bot = Point {x, y};
 
-        let x = x2.parse::<f32>().map_err(
-            |_| ParseRectangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", x2).to_string(),
-            })?;
-        let y = y2.parse::<f32>().map_err(
-            |_| ParseRectangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", y2).to_string(),
-            })?;
+        let x = params.next_float()?;
+        let y = params.next_float()?;
         let right_top = Point {x, y};
 
-        if right_top.x < left_bot.x || right_top.y < left_bot.y {
-            return Err(ParseRectangleError {
-                place: s.to_string(),
-                cause: "right top point coords must be greater than left bot point".to_string(),
-            })
-        }
-        if right_top == left_bot {
-            return Err(ParseRectangleError {
-                place: s.to_string(),
-                cause: "corners must be different".to_string(),
-            })
-        }
+        let rectangle = Rectangle::new(left_bot, right_top);
+        rectangle.validate().map_err(|err| params.invalid(err))?;
 
-        Ok(Rectangle::new(left_bot, right_top))
+        Ok(rectangle)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::polygon::Polygon;
 
     #[test]
     fn parse_correct_rectangle() {
@@ -218,6 +318,8 @@ mod tests {
         let rectangle = Rectangle {
             left_bot: Point { x: -1.0, y: -1.0},
             right_top: Point { x: 2.0, y: 5.0},
+            angle: 0.0,
+            shear: 0.0,
         };
 
         assert_eq!(
@@ -235,6 +337,8 @@ mod tests {
         let mut rectangle = Rectangle {
             left_bot: Point { x: -1.0, y: -1.0},
             right_top: Point { x: 2.0, y: 5.0},
+            angle: 0.0,
+            shear: 0.0,
         };
 
         rectangle.move_at(&Point {x:4.0, y:3.0});
@@ -249,6 +353,8 @@ mod tests {
         let mut rectangle = Rectangle {
             left_bot: Point { x: -1.0, y: -1.0},
             right_top: Point { x: 2.0, y: 5.0},
+            angle: 0.0,
+            shear: 0.0,
         };
 
         rectangle.move_by(3.5, 1.0);
@@ -262,6 +368,8 @@ mod tests {
         let rectangle = Rectangle {
             left_bot: Point { x: -1.0, y: -1.0},
             right_top: Point { x: 2.0, y: 5.0},
+            angle: 0.0,
+            shear: 0.0,
         };
 
         assert_eq!(
@@ -275,6 +383,8 @@ mod tests {
         let rectangle = Rectangle {
             left_bot: Point { x: -1.0, y: -1.0},
             right_top: Point { x: 2.0, y: 5.0},
+            angle: 0.0,
+            shear: 0.0,
         };
 
         assert_eq!(
@@ -288,6 +398,8 @@ mod tests {
         let mut rectangle = Rectangle {
             left_bot: Point { x: 2.0, y: 2.0},
             right_top: Point { x: 6.0, y: 5.0},
+            angle: 0.0,
+            shear: 0.0,
         };
 
         rectangle.scale(2.0);
@@ -295,4 +407,170 @@ mod tests {
         assert_eq!(Point { x: 0.0, y: 0.5}, rectangle.left_bot);
         assert_eq!(Point { x: 8.0, y: 6.5}, rectangle.right_top);
     }
+
+    #[test]
+    fn scale_xy_correct() {
+        let mut rectangle = Rectangle::new(
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 6.0, y: 5.0 },
+        );
+
+        rectangle.scale_xy(0.5, 2.0);
+
+        assert_eq!(Point { x: 3.0, y: 0.5 }, rectangle.left_bot);
+        assert_eq!(Point { x: 5.0, y: 6.5 }, rectangle.right_top);
+    }
+
+    #[test]
+    fn scale_xy_rotated_shears() {
+        let mut rectangle = Rectangle::new(
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 2.0 },
+        );
+        rectangle.rotate(45.0);
+
+        rectangle.scale_xy(2.0, 1.0);
+
+        assert_relative_eq!(8.0, rectangle.get_area(), epsilon = 1e-5);
+        assert!(rectangle.shear.abs() > 0.1);
+    }
+
+    #[test]
+    fn rotate_keeps_center_and_area() {
+        let mut rectangle = Rectangle::new(
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 2.0 },
+        );
+
+        rectangle.rotate(30.0);
+
+        assert_eq!(Point { x: 2.0, y: 1.0 }, rectangle.get_center());
+        assert_eq!(8.0, rectangle.get_area());
+    }
+
+    #[test]
+    fn get_frame_rect_rotated() {
+        let mut rectangle = Rectangle::new(
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 2.0 },
+        );
+
+        rectangle.rotate(90.0);
+        let frame = rectangle.get_frame_rect();
+
+        assert_relative_eq!(2.0, frame.width, epsilon = 1e-5);
+        assert_relative_eq!(4.0, frame.height, epsilon = 1e-5);
+        assert_relative_eq!(2.0, frame.pos.x, epsilon = 1e-5);
+        assert_relative_eq!(1.0, frame.pos.y, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn rotate_about_moves_center() {
+        let mut rectangle = Rectangle::new(
+            Point { x: 1.0, y: -1.0 },
+            Point { x: 3.0, y: 1.0 },
+        );
+
+        rectangle.rotate_about(&ORIGIN, 90.0);
+        let center = rectangle.get_center();
+
+        assert_relative_eq!(0.0, center.x, epsilon = 1e-6);
+        assert_relative_eq!(2.0, center.y, epsilon = 1e-6);
+        assert_eq!(90.0, rectangle.angle);
+    }
+
+    #[test]
+    fn transform_shear_makes_parallelogram() {
+        let mut rectangle = Rectangle::new(
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 2.0 },
+        );
+
+        rectangle.transform(&Affine2::shear(1.0, 0.0));
+        let corners = rectangle.corners();
+
+        assert_eq!(4.0, rectangle.get_area());
+        assert_eq!(Point { x: 0.0, y: 0.0 }, corners[0]);
+        assert_eq!(Point { x: 4.0, y: 2.0 }, corners[2]);
+        assert_eq!(Rect { pos: Point { x: 2.0, y: 1.0 }, width: 4.0, height: 2.0 }, rectangle.get_frame_rect());
+        assert!(matches!(rectangle.get_outline(), Outline::Polygon(_)));
+    }
+
+    #[test]
+    fn transform_rotation_matches_rotate() {
+        let mut rotated = Rectangle::new(
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 2.0 },
+        );
+        let mut transformed = rotated.clone();
+
+        rotated.rotate(30.0);
+        transformed.transform(&Affine2::rotation(30.0).about(&transformed.get_center()));
+
+        assert_relative_eq!(30.0, transformed.angle, epsilon = 1e-4);
+        assert_relative_eq!(0.0, transformed.shear, epsilon = 1e-6);
+        for (expected, corner) in rotated.corners().iter().zip(transformed.corners()) {
+            assert_relative_eq!(expected.x, corner.x, epsilon = 1e-5);
+            assert_relative_eq!(expected.y, corner.y, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn transform_reflection_keeps_shape() {
+        let mut rectangle = Rectangle::new(
+            Point { x: 1.0, y: 0.0 },
+            Point { x: 3.0, y: 1.0 },
+        );
+
+        rectangle.transform(&Affine2::scaling(-1.0, 1.0));
+        let frame = rectangle.get_frame_rect();
+
+        assert_eq!(2.0, rectangle.get_area());
+        assert_relative_eq!(-2.0, frame.pos.x, epsilon = 1e-5);
+        assert_relative_eq!(0.5, frame.pos.y, epsilon = 1e-5);
+        assert_relative_eq!(2.0, frame.width, epsilon = 1e-5);
+        assert_relative_eq!(1.0, frame.height, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_geometry() {
+        let cause = |s: &str| s.parse::<Rectangle>().unwrap_err().cause.into_string();
+
+        assert_eq!(ValidationError::InvertedCorners.to_string(), cause("1.0 2.0 -1.0 -12.0"));
+        assert_eq!(ValidationError::ZeroArea.to_string(), cause("-1.0 -12.0 -1.0 -12.0"));
+        assert_eq!(ValidationError::ZeroArea.to_string(), cause("0 0 0 5"));
+        assert_eq!(ValidationError::NotFinite.to_string(), cause("0 0 inf 5"));
+        assert_eq!(ValidationError::Overflow.to_string(), cause("0 0 1e19 5"));
+    }
+
+    #[test]
+    fn normalized_swaps_sides_of_turned_rectangle() {
+        let rectangle = Rectangle {
+            left_bot: Point { x: 3.0, y: 2.0 },
+            right_top: Point { x: -1.0, y: 0.0 },
+            angle: -270.0,
+            shear: 0.0,
+        };
+
+        let normalized = rectangle.normalized();
+
+        assert_eq!(0.0, normalized.angle);
+        assert_eq!(Point { x: 0.0, y: -1.0 }, normalized.left_bot);
+        assert_eq!(Point { x: 2.0, y: 3.0 }, normalized.right_top);
+        assert!(!normalized.approx_eq(&Rectangle::new(rectangle.right_top, rectangle.left_bot), 1e-4));
+        assert!(normalized.approx_eq(&"0 -1 2 3".parse::<Rectangle>().unwrap(), 1e-4));
+    }
+
+    #[test]
+    fn approx_eq_after_scaling_back() {
+        let mut rectangle: Rectangle = "0.1 0.2 1.3 2.7".parse().unwrap();
+        rectangle.rotate(33.0);
+        let original = rectangle.clone();
+
+        rectangle.scale(3.0);
+        rectangle.scale(1.0 / 3.0);
+
+        assert!(rectangle.approx_eq(&original, 1e-4));
+        assert!(rectangle.approx_eq(&Polygon::new(original.corners().to_vec()), 1e-4));
+    }
 }
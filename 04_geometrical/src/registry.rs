@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::ellipse::Ellipse;
+use crate::errors::{Diagnostic, ErrorKind};
+use crate::polygon::Polygon;
+use crate::rectangle::Rectangle;
+use crate::scene::COMMANDS;
+use crate::shape::Shape;
+use crate::triangle::Triangle;
+
+/// Разбор параметров фигуры -- всего, что в строке после ключевого слова
+pub type ShapeParser = Box<dyn Fn(&str) -> Result<Box<dyn Shape>, Diagnostic>>;
+
+///
+/// # Ключевые слова фигур и их разбор
+///
+/// По умолчанию содержит `RECTANGLE`, `TRIANGLE`, `ELLIPSE` и `POLYGON`.
+/// Новые виды фигур добавляются через `register` без изменения
+/// сценария: `SQUARE x y side` может разбираться в `Rectangle`
+///
+pub struct ShapeRegistry {
+    parsers: BTreeMap<&'static str, ShapeParser>,
+}
+
+impl ShapeRegistry {
+    /// # Реестр без фигур
+    pub fn empty() -> ShapeRegistry {
+        ShapeRegistry { parsers: BTreeMap::new() }
+    }
+
+    /// # Зарегистрировать разбор фигуры
+    ///
+    /// Заменяет разбор, ранее зарегистрированный для `keyword`.
+    /// Ключевые слова команд сценария (`scene::COMMANDS`) заняты:
+    /// сцена проверяет их раньше фигур, поэтому такая фигура никогда
+    /// не разбиралась бы
+    pub fn register<F>(&mut self, keyword: &'static str, parser: F) -> Result<(), Diagnostic>
+    where
+        F: Fn(&str) -> Result<Box<dyn Shape>, Diagnostic> + 'static,
+    {
+        if COMMANDS.contains(&keyword) {
+            return Err(Diagnostic::new(
+                "",
+                keyword,
+                ErrorKind::ReservedKeyword,
+                "Keyword is reserved for a command".to_string(),
+            ));
+        }
+        self.parsers.insert(keyword, Box::new(parser));
+        Ok(())
+    }
+
+    /// # Зарегистрировать фигуру, которая разбирается через `FromStr`
+    pub fn register_type<T>(&mut self, keyword: &'static str) -> Result<(), Diagnostic>
+    where
+        T: Shape + FromStr<Err = Diagnostic> + 'static,
+    {
+        self.register(keyword, |s| Ok(Box::new(s.parse::<T>()?)))
+    }
+
+    /// # Разобрать фигуру
+    ///
+    /// `None`, если `keyword` не зарегистрирован. Разобранная фигура
+    /// проверяется через `Validate`, так что зарегистрированным разборам
+    /// проверять её не обязательно
+    pub fn parse(&self, keyword: &str, params: &str) -> Option<Result<Box<dyn Shape>, Diagnostic>> {
+        let (&keyword, parser) = self.parsers.get_key_value(keyword)?;
+        Some(parser(params).and_then(|shape| match shape.validate() {
+            Ok(()) => Ok(shape),
+            Err(err) => Err(Diagnostic::new(keyword, params, ErrorKind::InvalidGeometry, err.to_string())),
+        }))
+    }
+
+    /// # Зарегистрированные ключевые слова в алфавитном порядке
+    pub fn keywords(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.parsers.keys().copied()
+    }
+}
+
+impl Default for ShapeRegistry {
+    fn default() -> Self {
+        let mut registry = ShapeRegistry::empty();
+        for result in [
+            registry.register_type::<Rectangle>("RECTANGLE"),
+            registry.register_type::<Triangle>("TRIANGLE"),
+            registry.register_type::<Ellipse>("ELLIPSE"),
+            registry.register_type::<Polygon>("POLYGON"),
+        ] {
+            result.expect("shape keywords are not commands");
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_types::{Float, Point};
+
+    #[test]
+    fn builtin_shapes() {
+        let registry = ShapeRegistry::default();
+
+        let rectangle = registry.parse("RECTANGLE", "0 0 2 3").unwrap().unwrap();
+        let error = registry.parse("ELLIPSE", "0 0 -1 1").unwrap().unwrap_err();
+
+        assert_eq!(6.0, rectangle.get_area());
+        assert_eq!(ErrorKind::InvalidGeometry, error.kind);
+        assert!(registry.parse("CIRCLE", "0 0 1").is_none());
+        assert_eq!(
+            vec!["ELLIPSE", "POLYGON", "RECTANGLE", "TRIANGLE"],
+            registry.keywords().collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn register_custom_keyword() {
+        let mut registry = ShapeRegistry::empty();
+        registry.register("SQUARE", |s| {
+            // SQUARE x y side -- квадрат с левым нижним углом в (x, y)
+            let values: Vec<Float> = s
+                .split_whitespace()
+                .map(|token| token.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| Diagnostic::new("SQUARE", s, ErrorKind::InvalidNumber, "Not a number".into()))?;
+            let [x, y, side] = values[..] else {
+                return Err(Diagnostic::new("SQUARE", s, ErrorKind::WrongParameterCount, "Need 3".into()));
+            };
+            Ok(Box::new(Rectangle {
+                left_bot: Point { x, y },
+                right_top: Point { x: x + side, y: y + side },
+                angle: 0.0,
+                shear: 0.0,
+            }))
+        }).unwrap();
+
+        let square = registry.parse("SQUARE", "1 1 2").unwrap().unwrap();
+
+        assert_eq!(4.0, square.get_area());
+        assert_eq!(Point { x: 2.0, y: 2.0 }, square.get_center());
+        assert_eq!(
+            "shape has zero area",
+            &*registry.parse("SQUARE", "1 1 0").unwrap().unwrap_err().cause,
+        );
+        assert!(registry.parse("RECTANGLE", "0 0 1 1").is_none());
+    }
+
+    #[test]
+    fn command_keywords_are_reserved() {
+        let mut registry = ShapeRegistry::default();
+
+        let error = registry.register_type::<Rectangle>("SCALE").unwrap_err();
+
+        assert_eq!(ErrorKind::ReservedKeyword, error.kind);
+        assert_eq!("Cannot register `SCALE': Keyword is reserved for a command", error.to_string());
+        assert!(registry.register_type::<Rectangle>("UNDO").is_err());
+        assert!(registry.parse("SCALE", "0 0 1 1").is_none());
+    }
+}
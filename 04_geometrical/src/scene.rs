@@ -0,0 +1,595 @@
+use std::io;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use crate::base_types::{Float, Rect};
+use crate::commands::{Area, Contains, ContainsTarget, Dedup, Delete, Enclose, Intersects, Move, Rotate, Scale, Select};
+use crate::errors::{Diagnostic, ErrorKind};
+use crate::group::Group;
+use crate::history::{History, Operation};
+use crate::registry::ShapeRegistry;
+use crate::shape::Shape;
+use crate::validate::ValidationError;
+
+/// Ключевые слова команд сценария. Ключевые слова фигур берутся
+/// из `ShapeRegistry` и не могут совпадать с командами
+pub const COMMANDS: [&str; 16] = [
+    "GROUP", "END", "SCALE", "MOVE", "ROTATE", "PRINT", "MEASURE", "AREA", "DELETE",
+    "DEDUP", "SELECT", "INTERSECTS", "CONTAINS", "ENCLOSE", "UNDO", "REDO",
+];
+
+///
+/// # Результат строки сценария, который нужно показать пользователю
+///
+/// Сцена ничего не выводит сама: как показать состояние фигур,
+/// решает вызывающий код
+///
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// `SCALE` выполнен, `before` -- фигуры до масштабирования,
+    /// после -- `Scene::shapes()`
+    Scaled { before: Vec<Box<dyn Shape>> },
+    /// `PRINT`: вывести состояние фигур
+    Print,
+    /// `MEASURE`: вывести характеристики фигур
+    Measure,
+    /// `AREA`: вывести сумму площадей и площадь объединения
+    Area(Area),
+    /// Ответ на `INTERSECTS` или `CONTAINS`
+    Answer(bool),
+    /// Фигура добавлена в сцену под номером `index`: описана вне
+    /// группы, закрыта группа или построена `ENCLOSE`
+    Added { index: usize },
+}
+
+///
+/// # Сцена: фигуры и выполненные над ними команды
+///
+/// Строки сценария выполняются по одной методом `execute`, ошибки
+/// не прерывают выполнение, а накапливаются в `errors()`.
+/// `Scene::parse` выполняет весь сценарий сразу. Фигуры разбираются
+/// через `ShapeRegistry`, свои виды фигур подключаются через
+/// `Scene::with_registry`
+///
+pub struct Scene {
+    registry: ShapeRegistry,
+    history: History,
+    /// Открытые группы вместе с номерами строк `GROUP`
+    groups: Vec<(usize, Group)>,
+    /// Фигуры, выбранные `SELECT`, `None` -- все фигуры
+    selected: Option<Vec<usize>>,
+    errors: Vec<Diagnostic>,
+}
+
+fn unbalanced_group(keyword: &'static str, cause: &str) -> Diagnostic {
+    Diagnostic::new(keyword, "", ErrorKind::UnbalancedGroup, cause.to_string())
+}
+
+fn no_such_shape(keyword: &'static str, place: &str, index: usize) -> Diagnostic {
+    Diagnostic::new(
+        keyword,
+        place,
+        ErrorKind::NoSuchShape,
+        format!("No shape with index {index}"),
+    )
+}
+
+/// Операция отклонена: фигура `index` после неё некорректна
+fn transform_rejected(keyword: &'static str, place: &str, (index, error): (usize, ValidationError)) -> Diagnostic {
+    Diagnostic::new(
+        keyword,
+        place,
+        ErrorKind::RejectedTransform { index, error },
+        error.to_string(),
+    )
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene::with_registry(ShapeRegistry::default())
+    }
+
+    /// # Пустая сцена со своим набором фигур
+    pub fn with_registry(registry: ShapeRegistry) -> Scene {
+        Scene {
+            registry,
+            history: History::new(Vec::new()),
+            groups: Vec::new(),
+            selected: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// # Выполнить сценарий целиком
+    ///
+    /// Результаты запросов (`PRINT`, `INTERSECTS`, ...) отбрасываются,
+    /// незакрытые группы попадают в `errors()`
+    pub fn parse<R: BufRead>(reader: R) -> io::Result<Scene> {
+        Scene::parse_with(reader, ShapeRegistry::default())
+    }
+
+    /// # Выполнить сценарий со своим набором фигур
+    pub fn parse_with<R: BufRead>(reader: R, registry: ShapeRegistry) -> io::Result<Scene> {
+        let mut scene = Scene::with_registry(registry);
+        for (number, line) in reader.lines().enumerate() {
+            scene.execute(number + 1, &line?);
+        }
+        scene.finish();
+        Ok(scene)
+    }
+
+    /// # Текущие фигуры в порядке описания
+    pub fn shapes(&self) -> &[Box<dyn Shape>] {
+        self.history.shapes()
+    }
+
+    /// # Ошибки в выполненных строках
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+
+    /// # Суммарная площадь фигур
+    ///
+    /// Перекрытия считаются дважды, см. `union::union_area`
+    pub fn total_area(&self) -> Float {
+        self.shapes().iter().map(|shape| shape.get_area()).sum()
+    }
+
+    /// # Ограничивающие прямоугольники фигур
+    pub fn frame_rects(&self) -> Vec<Rect> {
+        self.shapes().iter().map(|shape| shape.get_frame_rect()).collect()
+    }
+
+    /// # Добавить фигуру
+    ///
+    /// В последнюю открытую группу или, если групп нет, в сцену
+    pub fn add_shape(&mut self, shape: Box<dyn Shape>) {
+        match self.groups.last_mut() {
+            Some((_, group)) => group.push(shape),
+            None => self.history.push_shape(shape),
+        }
+    }
+
+    /// Добавить фигуру, событие -- если она попала в сцену, а не в группу
+    fn add_event(&mut self, shape: Box<dyn Shape>) -> Option<Event> {
+        let nested = !self.groups.is_empty();
+        self.add_shape(shape);
+        (!nested).then(|| Event::Added { index: self.shapes().len() - 1 })
+    }
+
+    /// # Масштабировать выбранные фигуры
+    ///
+    /// Если какая-то фигура после масштабирования некорректна,
+    /// фигуры не меняются
+    pub fn apply(&mut self, scale: Scale) -> Result<(), Diagnostic> {
+        let targets = self.targets();
+        self.operate("SCALE", "", Operation::Scale { targets, scale })
+    }
+
+    /// Применить операцию через историю, отказ -- в диагностику
+    fn operate(&mut self, keyword: &'static str, params: &str, operation: Operation) -> Result<(), Diagnostic> {
+        self.history
+            .apply(operation)
+            .map_err(|rejected| transform_rejected(keyword, params, rejected))
+    }
+
+    /// Записать ошибку команды в `errors`
+    fn check(&mut self, result: Result<(), Diagnostic>, number: usize, offset: usize) -> Option<()> {
+        result.map_err(|err| self.errors.push(err.locate(number, offset))).ok()
+    }
+
+    /// # Удалить повторяющиеся фигуры
+    ///
+    /// Фигуры сравниваются по контуру с допуском `epsilon`, из равных
+    /// остаётся первая. Удаление можно отменить через `UNDO`.
+    /// Возвращает число удалённых фигур
+    pub fn dedup(&mut self, epsilon: Float) -> usize {
+        let count = self.shapes().len();
+        // Удаление не меняет оставшиеся фигуры, поэтому всегда проходит проверку
+        self.history.apply(Operation::Dedup { epsilon }).expect("DEDUP changes no shapes");
+        let removed = count - self.shapes().len();
+        // Номера оставшихся фигур сдвинулись
+        if removed > 0 {
+            self.selected = None;
+        }
+        removed
+    }
+
+    /// Номера фигур, к которым применяется команда
+    fn targets(&self) -> Vec<usize> {
+        match &self.selected {
+            Some(indices) => indices.clone(),
+            None => (0..self.shapes().len()).collect(),
+        }
+    }
+
+    /// Разобрать параметры команды, ошибку записать в `errors`
+    fn parse_params<T>(&mut self, params: &str, number: usize, offset: usize) -> Option<T>
+    where
+        T: FromStr<Err = Diagnostic>,
+    {
+        match params.parse() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors.push(err.locate(number, offset));
+                None
+            },
+        }
+    }
+
+    /// # Выполнить строку сценария с номером `number`
+    ///
+    /// Возвращает, что показать пользователю, если строка -- запрос,
+    /// `SCALE` или добавление фигуры. Ошибки записываются в `errors()`
+    pub fn execute(&mut self, number: usize, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            return None;
+        }
+        let (name, params) = line.split_once(' ').unwrap_or((line, ""));
+        // Позиции в диагностике отсчитываются от начала параметров
+        let offset = name.chars().count() + 1;
+        match name {
+            "GROUP" => self.groups.push((number, Group::default())),
+            "END" => {
+                let Some((_, group)) = self.groups.pop() else {
+                    self.errors.push(unbalanced_group("END", "END without GROUP").locate(number, 0));
+                    return None;
+                };
+                if group.children.is_empty() {
+                    self.errors.push(unbalanced_group("GROUP", "Empty group").locate(number, 0));
+                    return None;
+                }
+                return self.add_event(Box::new(group));
+            },
+            "ROTATE" => {
+                let rotate: Rotate = self.parse_params(params, number, offset)?;
+                let operation = Operation::Rotate {
+                    targets: self.targets(),
+                    pivot: rotate.origin,
+                    angle: rotate.angle,
+                };
+                let result = self.operate("ROTATE", params, operation);
+                self.check(result, number, offset)?;
+            },
+            "MOVE" => {
+                let delta: Move = self.parse_params(params, number, offset)?;
+                let operation = Operation::MoveBy {
+                    targets: self.targets(),
+                    dx: delta.dx,
+                    dy: delta.dy,
+                };
+                let result = self.operate("MOVE", params, operation);
+                self.check(result, number, offset)?;
+            },
+            "SCALE" => {
+                let scale: Scale = self.parse_params(params, number, offset)?;
+                let before = self.shapes().to_vec();
+                let operation = Operation::Scale { targets: self.targets(), scale };
+                let result = self.operate("SCALE", params, operation);
+                self.check(result, number, offset)?;
+                return Some(Event::Scaled { before });
+            },
+            "PRINT" => return Some(Event::Print),
+            "MEASURE" => return Some(Event::Measure),
+            "AREA" => return self.parse_params(params, number, offset).map(Event::Area),
+            "DELETE" => {
+                let delete: Delete = self.parse_params(params, number, offset)?;
+                if delete.index >= self.shapes().len() {
+                    self.errors.push(no_such_shape("DELETE", params, delete.index).locate(number, offset));
+                    return None;
+                }
+                let result = self.operate("DELETE", params, Operation::Delete(delete.index));
+                self.check(result, number, offset)?;
+                if let Some(indices) = &mut self.selected {
+                    indices.retain(|&i| i != delete.index);
+                    for i in indices.iter_mut() {
+                        if *i > delete.index {
+                            *i -= 1;
+                        }
+                    }
+                }
+            },
+            "DEDUP" => {
+                let dedup: Dedup = self.parse_params(params, number, offset)?;
+                self.dedup(dedup.epsilon);
+            },
+            "SELECT" => {
+                let select: Select = self.parse_params(params, number, offset)?;
+                if let Some(index) = select.indices.iter().find(|&&i| i >= self.shapes().len()) {
+                    self.errors.push(no_such_shape("SELECT", params, *index).locate(number, offset));
+                    return None;
+                }
+                self.selected = if select.indices.is_empty() {
+                    None
+                } else {
+                    Some(select.indices)
+                };
+            },
+            "INTERSECTS" => {
+                let query: Intersects = self.parse_params(params, number, offset)?;
+                let shapes = self.shapes();
+                let (Some(first), Some(second)) = (shapes.get(query.first), shapes.get(query.second)) else {
+                    let index = if query.first >= shapes.len() { query.first } else { query.second };
+                    self.errors.push(no_such_shape("INTERSECTS", params, index).locate(number, offset));
+                    return None;
+                };
+                return Some(Event::Answer(first.intersects(second.as_ref())));
+            },
+            "CONTAINS" => {
+                let query: Contains = self.parse_params(params, number, offset)?;
+                let shapes = self.shapes();
+                let Some(shape) = shapes.get(query.index) else {
+                    self.errors.push(no_such_shape("CONTAINS", params, query.index).locate(number, offset));
+                    return None;
+                };
+                let answer = match query.target {
+                    ContainsTarget::Point(point) => shape.contains_point(&point),
+                    ContainsTarget::Shape(index) => {
+                        let Some(other) = shapes.get(index) else {
+                            self.errors.push(no_such_shape("CONTAINS", params, index).locate(number, offset));
+                            return None;
+                        };
+                        shape.contains(other.as_ref())
+                    },
+                };
+                return Some(Event::Answer(answer));
+            },
+            "ENCLOSE" => {
+                let enclose: Enclose = self.parse_params(params, number, offset)?;
+                let selected: Vec<Box<dyn Shape>> = self
+                    .targets()
+                    .iter()
+                    .filter_map(|&i| self.shapes().get(i).cloned())
+                    .collect();
+                let Some(shape) = enclose.kind.build(&selected, enclose.tolerance) else {
+                    self.errors.push(
+                        Diagnostic::new(
+                            "ENCLOSE",
+                            params,
+                            ErrorKind::InvalidGeometry,
+                            "Shapes are empty or degenerate".to_string(),
+                        ).locate(number, offset)
+                    );
+                    return None;
+                };
+                return self.add_event(shape);
+            },
+            "UNDO" | "REDO" => {
+                let count = self.shapes().len();
+                let done = if name == "UNDO" {
+                    self.history.undo()
+                } else {
+                    match self.history.redo() {
+                        Ok(done) => done,
+                        Err(rejected) => {
+                            self.errors.push(transform_rejected("REDO", params, rejected).locate(number, offset));
+                            return None;
+                        },
+                    }
+                };
+                if !done {
+                    let (keyword, cause) = if name == "UNDO" {
+                        ("UNDO", "Nothing to undo")
+                    } else {
+                        ("REDO", "Nothing to redo")
+                    };
+                    self.errors.push(
+                        Diagnostic::new(keyword, params, ErrorKind::EmptyHistory, cause.to_string())
+                            .locate(number, 0)
+                    );
+                    return None;
+                }
+                // Отмена или возврат DELETE и DEDUP сдвигает номера фигур
+                if self.shapes().len() != count {
+                    self.selected = None;
+                }
+            },
+            _ => match self.registry.parse(name, params) {
+                Some(Ok(shape)) => return self.add_event(shape),
+                Some(Err(err)) => self.errors.push(err.locate(number, offset)),
+                None => {
+                    let known: Vec<&'static str> = self.registry.keywords().chain(COMMANDS).collect();
+                    self.errors.push(Diagnostic::unknown_keyword(name, &known).locate(number, 0));
+                },
+            },
+        }
+        None
+    }
+
+    /// # Завершить сценарий
+    ///
+    /// Записывает ошибки для незакрытых групп, их фигуры в сцену
+    /// не попадают
+    pub fn finish(&mut self) {
+        for (number, _) in self.groups.drain(..) {
+            self.errors.push(unbalanced_group("GROUP", "GROUP without END").locate(number, 0));
+        }
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_types::Point;
+    use crate::base_types::consts::PI;
+    use crate::ellipse::Ellipse;
+    use crate::outline::DEFAULT_EPSILON;
+
+    const SCRIPT: &str = "\
+RECTANGLE 0 0 2 2
+GROUP
+TRIANGLE 0 0 1 0 0 1
+ELLIPSE 5 5 1 1
+END
+CIRCLE 1 1 1
+SELECT 0
+SCALE 0 0 2
+";
+
+    #[test]
+    fn parse_script() {
+        let scene = Scene::parse(SCRIPT.as_bytes()).unwrap();
+
+        assert_eq!(2, scene.shapes().len());
+        assert_relative_eq!(16.0 + 0.5 + PI, scene.total_area(), epsilon = 1e-5);
+        assert_eq!(
+            Rect { pos: Point { x: 2.0, y: 2.0 }, width: 4.0, height: 4.0 },
+            scene.frame_rects()[0],
+        );
+        assert_eq!(1, scene.errors().len());
+        assert_eq!(ErrorKind::UnknownKeyword, scene.errors()[0].kind);
+        assert_eq!(Some(6), scene.errors()[0].line);
+    }
+
+    #[test]
+    fn apply_to_selection() {
+        let mut scene = Scene::parse(SCRIPT.as_bytes()).unwrap();
+
+        scene.apply("CENTER 0.5".parse().unwrap()).unwrap();
+
+        assert_eq!(4.0, scene.shapes()[0].get_area());
+        scene.execute(1, "SELECT");
+        scene.apply("CENTER 2".parse().unwrap()).unwrap();
+        assert_eq!(16.0, scene.shapes()[0].get_area());
+    }
+
+    #[test]
+    fn execute_reports_events() {
+        let mut scene = Scene::new();
+        scene.execute(1, "RECTANGLE 0 0 2 2");
+
+        let Some(Event::Scaled { before }) = scene.execute(2, "SCALE 0 0 3") else {
+            panic!("SCALE should report the previous state");
+        };
+        assert_eq!(4.0, before[0].get_area());
+        assert!(matches!(scene.execute(3, "CONTAINS 0 5 5"), Some(Event::Answer(true))));
+        assert!(scene.execute(4, "CONTAINS 1 5 5").is_none());
+        assert!(matches!(scene.execute(5, "RECTANGLE 1 1 2 2"), Some(Event::Added { index: 1 })));
+        assert!(scene.execute(6, "GROUP").is_none());
+        assert!(scene.execute(7, "RECTANGLE 1 1 2 2").is_none());
+        assert!(matches!(scene.execute(8, "END"), Some(Event::Added { index: 2 })));
+        assert_eq!(ErrorKind::NoSuchShape, scene.errors()[0].kind);
+    }
+
+    #[test]
+    fn unclosed_group() {
+        let scene = Scene::parse("GROUP\nRECTANGLE 0 0 1 1\nEND\nEND\nGROUP\n".as_bytes()).unwrap();
+
+        let errors: Vec<(Option<usize>, &str)> = scene
+            .errors()
+            .iter()
+            .map(|error| (error.line, error.cause.as_ref()))
+            .collect();
+        assert_eq!(vec![(Some(4), "END without GROUP"), (Some(5), "GROUP without END")], errors);
+        assert_eq!(1, scene.shapes().len());
+    }
+
+    #[test]
+    fn custom_registry() {
+        let mut registry = ShapeRegistry::default();
+        registry.register("CIRCLE", |s| {
+            // CIRCLE x y radius -- эллипс с равными радиусами
+            let (center, radius) = s.rsplit_once(' ').unwrap_or((s, ""));
+            Ok(Box::new(format!("{center} {radius} {radius}").parse::<Ellipse>()?))
+        }).unwrap();
+
+        let scene = Scene::parse_with("CIRCLE 0 0 1\nSQUARE 0 0 1\n".as_bytes(), registry).unwrap();
+
+        assert_eq!(1, scene.shapes().len());
+        assert_relative_eq!(PI, scene.total_area(), epsilon = 1e-5);
+        assert_eq!(ErrorKind::UnknownKeyword, scene.errors()[0].kind);
+    }
+
+    #[test]
+    fn dedup_keeps_first_copy() {
+        let script = "\
+RECTANGLE 0 0 2 1
+TRIANGLE 0 0 1 0 0 1
+POLYGON 0 1 0 0 2 0 2 1
+TRIANGLE 0 1 0 0 1 0
+ELLIPSE 0 0 1 2
+DEDUP
+";
+        let mut scene = Scene::parse(script.as_bytes()).unwrap();
+
+        assert!(scene.errors().is_empty());
+        assert_eq!(3, scene.shapes().len());
+        assert_relative_eq!(2.0 + 0.5 + 2.0 * PI, scene.total_area(), epsilon = 1e-5);
+        scene.execute(7, "UNDO");
+        assert_eq!(5, scene.shapes().len());
+        assert_eq!(2, scene.dedup(DEFAULT_EPSILON));
+    }
+
+    #[test]
+    fn invalid_transform_is_reported() {
+        let mut scene = Scene::new();
+        scene.execute(1, "RECTANGLE 0 0 2 2");
+        scene.execute(2, "ELLIPSE 0 0 1 nan");
+
+        assert!(scene.execute(3, "SCALE CENTER 1e30").is_none());
+        scene.execute(4, "MOVE 1 1");
+
+        let errors: Vec<(Option<usize>, &str)> = scene
+            .errors()
+            .iter()
+            .map(|error| (error.line, error.cause.as_ref()))
+            .collect();
+        assert_eq!(
+            vec![
+                (Some(2), "coordinates must be finite numbers"),
+                (Some(3), "coordinates are too large"),
+            ],
+            errors,
+        );
+        assert_eq!(
+            "line 3, column 7: For SCALE `CENTER 1e30': SCALE would make shape 0 invalid: coordinates are too large",
+            scene.errors()[1].to_string(),
+        );
+        assert_eq!(Point { x: 2.0, y: 2.0 }, scene.shapes()[0].get_center());
+    }
+
+    #[test]
+    fn negative_scale_reflects_every_shape() {
+        let script = "\
+RECTANGLE 1 1 3 2
+TRIANGLE 1 1 2 1 1 2
+ELLIPSE 1 1 1 2
+POLYGON 1 1 2 1 2 2 1 2
+GROUP
+RECTANGLE 0 0 1 1
+END
+SCALE 0 0 -2
+";
+        let scene = Scene::parse(script.as_bytes()).unwrap();
+
+        assert!(scene.errors().is_empty(), "{:?}", scene.errors());
+        assert_relative_eq!(8.0 + 2.0 + 8.0 * PI + 4.0 + 4.0, scene.total_area(), epsilon = 1e-4);
+        assert_eq!(Point { x: -4.0, y: -3.0 }, scene.shapes()[0].get_center());
+        assert_eq!(Point { x: -2.0, y: -2.0 }, scene.shapes()[2].get_center());
+    }
+
+    #[test]
+    fn tiny_scale_keeps_every_shape_valid() {
+        let script = "\
+RECTANGLE 1 1 3 2
+TRIANGLE 1 1 2 1 1 2
+ELLIPSE 1 1 1 2
+POLYGON 1 1 2 1 2 2 1 3 0 2
+GROUP
+TRIANGLE 0 0 1 0 0 1
+END
+SCALE CENTER 1e-4
+";
+        let scene = Scene::parse(script.as_bytes()).unwrap();
+
+        assert!(scene.errors().is_empty(), "{:?}", scene.errors());
+        let area = 2.0 + 0.5 + 2.0 * PI + 2.5 + 0.5;
+        assert_relative_eq!(area * 1e-8, scene.total_area(), max_relative = 1e-3);
+    }
+}
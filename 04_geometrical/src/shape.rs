@@ -1,18 +1,134 @@
 use std::fmt;
 
 
-use crate::base_types::{Point, Rect};
+use crate::base_types::{Point, Rect, Moments, Float};
+use crate::outline::Outline;
+use crate::affine::Affine2;
+use crate::validate::Validate;
 
 
 ///
 /// # Shape abstract class aka Interface aka Trait
 ///
 ///
-pub trait Shape: fmt::Debug {
-    fn get_area(&self) -> f32;
+pub trait Shape: fmt::Debug + ShapeClone + Validate {
+    fn get_area(&self) -> Float;
     fn get_frame_rect(&self) -> Rect;
     fn move_at(&mut self, target: &Point);
-    fn move_by(&mut self, x: f32, y: f32);
-    fn scale(&mut self, factor: f32);
+    fn move_by(&mut self, x: Float, y: Float);
+    fn scale(&mut self, factor: Float);
     fn get_center(&self) -> Point;
+    fn get_outline(&self) -> Outline;
+
+    /// # Поворот вокруг собственного центра
+    ///
+    /// Угол в градусах, против часовой стрелки
+    fn rotate(&mut self, angle: Float);
+
+    /// # Масштабирование относительно собственного центра
+    ///
+    /// С разными коэффициентами по осям x и y
+    fn scale_xy(&mut self, sx: Float, sy: Float) {
+        let center = self.get_center();
+        self.transform(&Affine2::scaling(sx, sy).about(&center));
+    }
+
+    /// # Произвольное аффинное преобразование
+    ///
+    /// Включая неравномерное масштабирование, скос и отражение.
+    /// Фигура переходит в свой точный образ: прямоугольник может стать
+    /// параллелограммом, эллипс -- эллипсом с другими полуосями
+    fn transform(&mut self, affine: &Affine2);
+
+    /// # Поворот вокруг произвольной точки
+    ///
+    /// Центр фигуры поворачивается вокруг `pivot`, сама фигура
+    /// поворачивается на тот же угол вокруг своего центра
+    fn rotate_about(&mut self, pivot: &Point, angle: Float) {
+        let center = self.get_center().rotated(pivot, angle);
+        self.rotate(angle);
+        self.move_at(&center);
+    }
+
+    /// # Периметр
+    fn get_perimeter(&self) -> Float {
+        self.get_outline().perimeter()
+    }
+
+    /// # Центр масс
+    fn get_centroid(&self) -> Point {
+        self.get_outline().centroid()
+    }
+
+    /// # Моменты инерции площади относительно центра масс
+    fn get_moments(&self) -> Moments {
+        self.get_outline().moments()
+    }
+
+    /// # Принадлежность точки фигуре (включая границу)
+    fn contains_point(&self, point: &Point) -> bool {
+        self.get_outline().contains_point(point)
+    }
+
+    /// # Есть ли у фигур общие точки
+    fn intersects(&self, other: &dyn Shape) -> bool {
+        self.get_outline().intersects(&other.get_outline())
+    }
+
+    /// # Содержит ли фигура `other` целиком
+    fn contains(&self, other: &dyn Shape) -> bool {
+        self.get_outline().contains(&other.get_outline())
+    }
+
+    /// # Та же ли это фигура с допуском `epsilon`
+    ///
+    /// Фигуры разных типов могут быть равны, см. `Outline::approx_eq`
+    fn approx_eq(&self, other: &dyn Shape, epsilon: Float) -> bool {
+        self.get_outline().approx_eq(&other.get_outline(), epsilon)
+    }
+}
+
+///
+/// # Удалить повторы фигур
+///
+/// Из равных с допуском `epsilon` фигур остаётся первая.
+/// Возвращает число удалённых фигур
+///
+pub fn dedup(shapes: &mut Vec<Box<dyn Shape>>, epsilon: Float) -> usize {
+    let outlines: Vec<Outline> = shapes.iter().map(|shape| shape.get_outline()).collect();
+    let mut keep = Vec::with_capacity(outlines.len());
+    for (i, outline) in outlines.iter().enumerate() {
+        let duplicate = outlines[..i]
+            .iter()
+            .zip(&keep)
+            .any(|(earlier, &kept)| kept && earlier.approx_eq(outline, epsilon));
+        keep.push(!duplicate);
+    }
+
+    let before = shapes.len();
+    let mut keep = keep.into_iter();
+    shapes.retain(|_| keep.next().unwrap_or(true));
+    before - shapes.len()
+}
+
+
+///
+/// # Копирование фигуры за `Box<dyn Shape>`
+///
+/// Реализуется автоматически для всех фигур с `Clone`
+///
+pub trait ShapeClone {
+    fn clone_box(&self) -> Box<dyn Shape>;
+}
+
+impl<T: Shape + Clone + 'static> ShapeClone for T {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Shape> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
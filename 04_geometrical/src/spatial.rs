@@ -0,0 +1,593 @@
+use std::mem;
+
+use crate::base_types::{Float, Point, Rect};
+use crate::shape::Shape;
+
+/// Наибольшее и наименьшее число записей в узле R-дерева
+const MAX_ENTRIES: usize = 8;
+const MIN_ENTRIES: usize = 3;
+
+/// Прямоугольник, выровненный по осям, в виде углов
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    min: Point,
+    max: Point,
+}
+
+impl Bounds {
+    fn from_rect(rect: &Rect) -> Bounds {
+        Bounds {
+            min: rect.left_bot(),
+            max: rect.right_top(),
+        }
+    }
+
+    fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: Point {
+                x: self.min.x.min(other.min.x),
+                y: self.min.y.min(other.min.y),
+            },
+            max: Point {
+                x: self.max.x.max(other.max.x),
+                y: self.max.y.max(other.max.y),
+            },
+        }
+    }
+
+    fn area(&self) -> Float {
+        (self.max.x - self.min.x) * (self.max.y - self.min.y)
+    }
+
+    /// Насколько вырастет площадь, если добавить `other`
+    fn enlargement(&self, other: &Bounds) -> Float {
+        self.union(other).area() - self.area()
+    }
+
+    /// Пересечение с учётом касания границ
+    fn intersects(&self, other: &Bounds) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x
+            && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+
+    fn contains(&self, other: &Bounds) -> bool {
+        self.min.x <= other.min.x && other.max.x <= self.max.x
+            && self.min.y <= other.min.y && other.max.y <= self.max.y
+    }
+
+    /// Квадрат расстояния от точки до прямоугольника, ноль внутри
+    fn distance_squared(&self, point: &Point) -> Float {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        dx * dx + dy * dy
+    }
+}
+
+struct Entry<T> {
+    bounds: Bounds,
+    item: T,
+}
+
+/// Объединение прямоугольников непустого набора записей
+fn cover<T>(entries: &[Entry<T>]) -> Bounds {
+    entries
+        .iter()
+        .map(|entry| entry.bounds)
+        .reduce(|a, b| a.union(&b))
+        .expect("R-tree node is never empty")
+}
+
+/// # Квадратичное разбиение Гуттмана
+///
+/// Затравки -- пара записей, вместе занимающих больше всего лишней
+/// площади, остальные записи раздаются группе, которой они
+/// добавляют меньше площади
+fn split<T>(mut entries: Vec<Entry<T>>) -> (Vec<Entry<T>>, Vec<Entry<T>>) {
+    let mut seeds = (0, 1);
+    let mut worst = Float::MIN;
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            let waste = entries[i].bounds.union(&entries[j].bounds).area()
+                - entries[i].bounds.area()
+                - entries[j].bounds.area();
+            if waste > worst {
+                worst = waste;
+                seeds = (i, j);
+            }
+        }
+    }
+
+    // Сначала удаляется запись с большим номером, чтобы не сдвинуть вторую
+    let second = entries.swap_remove(seeds.1);
+    let first = entries.swap_remove(seeds.0);
+    let mut left_bounds = first.bounds;
+    let mut right_bounds = second.bounds;
+    let mut left = vec![first];
+    let mut right = vec![second];
+
+    while let Some(entry) = entries.pop() {
+        // Группа, которой не хватает записей, забирает все оставшиеся
+        let to_left = if left.len() + entries.len() < MIN_ENTRIES {
+            true
+        } else if right.len() + entries.len() < MIN_ENTRIES {
+            false
+        } else {
+            let left_growth = left_bounds.enlargement(&entry.bounds);
+            let right_growth = right_bounds.enlargement(&entry.bounds);
+            left_growth < right_growth
+                || (left_growth == right_growth && left.len() <= right.len())
+        };
+        if to_left {
+            left_bounds = left_bounds.union(&entry.bounds);
+            left.push(entry);
+        } else {
+            right_bounds = right_bounds.union(&entry.bounds);
+            right.push(entry);
+        }
+    }
+
+    (left, right)
+}
+
+enum Node {
+    Leaf(Vec<Entry<usize>>),
+    Branch(Vec<Entry<Node>>),
+}
+
+impl Node {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(entries) => entries.len(),
+            Node::Branch(children) => children.len(),
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        match self {
+            Node::Leaf(entries) => cover(entries),
+            Node::Branch(children) => cover(children),
+        }
+    }
+
+    /// Вставка в поддерево; при переполнении узел делится и
+    /// отделившаяся половина возвращается родителю
+    fn insert(&mut self, entry: Entry<usize>) -> Option<Node> {
+        match self {
+            Node::Leaf(entries) => {
+                entries.push(entry);
+                if entries.len() <= MAX_ENTRIES {
+                    return None;
+                }
+                let (left, right) = split(mem::take(entries));
+                *entries = left;
+                Some(Node::Leaf(right))
+            },
+            Node::Branch(children) => {
+                let best = (0..children.len())
+                    .min_by(|&a, &b| {
+                        let a = &children[a].bounds;
+                        let b = &children[b].bounds;
+                        a.enlargement(&entry.bounds)
+                            .total_cmp(&b.enlargement(&entry.bounds))
+                            .then(a.area().total_cmp(&b.area()))
+                    })
+                    .expect("R-tree node is never empty");
+
+                let child = &mut children[best];
+                child.bounds = child.bounds.union(&entry.bounds);
+                let sibling = child.item.insert(entry)?;
+                child.bounds = child.item.bounds();
+                children.push(Entry { bounds: sibling.bounds(), item: sibling });
+
+                if children.len() <= MAX_ENTRIES {
+                    return None;
+                }
+                let (left, right) = split(mem::take(children));
+                *children = left;
+                Some(Node::Branch(right))
+            },
+        }
+    }
+
+    /// Удаление записи `id`; записи опустевших узлов складываются в `orphans`
+    fn remove(&mut self, id: usize, bounds: &Bounds, orphans: &mut Vec<Entry<usize>>) -> bool {
+        match self {
+            Node::Leaf(entries) => {
+                let Some(position) = entries.iter().position(|entry| entry.item == id) else {
+                    return false;
+                };
+                entries.swap_remove(position);
+                true
+            },
+            Node::Branch(children) => {
+                for i in 0..children.len() {
+                    if !children[i].bounds.contains(bounds)
+                        || !children[i].item.remove(id, bounds, orphans) {
+                        continue;
+                    }
+                    if children[i].item.len() < MIN_ENTRIES {
+                        children.swap_remove(i).item.collect_entries(orphans);
+                    } else {
+                        children[i].bounds = children[i].item.bounds();
+                    }
+                    return true;
+                }
+                false
+            },
+        }
+    }
+
+    fn collect_entries(self, out: &mut Vec<Entry<usize>>) {
+        match self {
+            Node::Leaf(entries) => out.extend(entries),
+            Node::Branch(children) => {
+                for child in children {
+                    child.item.collect_entries(out);
+                }
+            },
+        }
+    }
+
+    fn query(&self, bounds: &Bounds, out: &mut Vec<usize>) {
+        match self {
+            Node::Leaf(entries) => out.extend(
+                entries
+                    .iter()
+                    .filter(|entry| entry.bounds.intersects(bounds))
+                    .map(|entry| entry.item),
+            ),
+            Node::Branch(children) => {
+                for child in children.iter().filter(|child| child.bounds.intersects(bounds)) {
+                    child.item.query(bounds, out);
+                }
+            },
+        }
+    }
+
+    /// Поиск с отсечением: поддерево не просматривается, если оно
+    /// дальше уже найденного кандидата `best` (квадрат расстояния и номер)
+    fn nearest(&self, point: &Point, best: &mut Option<(Float, usize)>) {
+        match self {
+            Node::Leaf(entries) => {
+                for entry in entries {
+                    let distance = entry.bounds.distance_squared(point);
+                    let closer = match *best {
+                        None => true,
+                        Some((best_distance, best_id)) => {
+                            distance < best_distance
+                                || (distance == best_distance && entry.item < best_id)
+                        },
+                    };
+                    if closer {
+                        *best = Some((distance, entry.item));
+                    }
+                }
+            },
+            Node::Branch(children) => {
+                let mut order: Vec<(Float, &Node)> = children
+                    .iter()
+                    .map(|child| (child.bounds.distance_squared(point), &child.item))
+                    .collect();
+                order.sort_by(|a, b| a.0.total_cmp(&b.0));
+                for (distance, child) in order {
+                    if best.is_some_and(|(best_distance, _)| distance > best_distance) {
+                        break;
+                    }
+                    child.nearest(point, best);
+                }
+            },
+        }
+    }
+}
+
+///
+/// # R-дерево номеров по ограничивающим прямоугольникам
+///
+/// Номера задаёт вызывающий код. Для удаления нужен тот же
+/// прямоугольник, с которым номер был вставлен
+///
+pub struct RTree {
+    root: Node,
+    len: usize,
+}
+
+impl RTree {
+    pub fn new() -> RTree {
+        RTree {
+            root: Node::Leaf(Vec::new()),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, id: usize, rect: &Rect) {
+        self.insert_entry(Entry { bounds: Bounds::from_rect(rect), item: id });
+        self.len += 1;
+    }
+
+    fn insert_entry(&mut self, entry: Entry<usize>) {
+        let Some(sibling) = self.root.insert(entry) else {
+            return;
+        };
+        // Корень разделился -- дерево растёт на уровень вверх
+        let old = mem::replace(&mut self.root, Node::Branch(Vec::new()));
+        self.root = Node::Branch(vec![
+            Entry { bounds: old.bounds(), item: old },
+            Entry { bounds: sibling.bounds(), item: sibling },
+        ]);
+    }
+
+    /// # Удалить номер `id`, вставленный с прямоугольником `rect`
+    ///
+    /// Возвращает `false`, если такой записи нет
+    pub fn remove(&mut self, id: usize, rect: &Rect) -> bool {
+        let mut orphans = Vec::new();
+        if !self.root.remove(id, &Bounds::from_rect(rect), &mut orphans) {
+            return false;
+        }
+        self.len -= 1;
+
+        loop {
+            match &mut self.root {
+                Node::Branch(children) if children.len() == 1 => {
+                    self.root = children.pop().expect("one child").item;
+                },
+                Node::Branch(children) if children.is_empty() => {
+                    self.root = Node::Leaf(Vec::new());
+                },
+                _ => break,
+            }
+        }
+
+        for orphan in orphans {
+            self.insert_entry(orphan);
+        }
+        true
+    }
+
+    /// # Номера, чьи прямоугольники пересекают `rect` (включая касание)
+    pub fn query(&self, rect: &Rect) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.root.query(&Bounds::from_rect(rect), &mut found);
+        found
+    }
+
+    /// # Номер с ближайшим к точке прямоугольником
+    ///
+    /// При равных расстояниях выбирается меньший номер
+    pub fn nearest(&self, point: &Point) -> Option<usize> {
+        let mut best = None;
+        self.root.nearest(point, &mut best);
+        best.map(|(_, id)| id)
+    }
+}
+
+impl Default for RTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Slot {
+    shape: Box<dyn Shape>,
+    frame: Rect,
+}
+
+///
+/// # Набор фигур с пространственным индексом
+///
+/// Фигуры получают постоянные номера при вставке. Изменять фигуры
+/// нужно через методы набора, чтобы индекс следовал за
+/// ограничивающими прямоугольниками
+///
+pub struct ShapeIndex {
+    slots: Vec<Option<Slot>>,
+    tree: RTree,
+}
+
+impl ShapeIndex {
+    pub fn new() -> ShapeIndex {
+        ShapeIndex {
+            slots: Vec::new(),
+            tree: RTree::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// # Добавить фигуру, возвращает её номер
+    pub fn insert(&mut self, shape: Box<dyn Shape>) -> usize {
+        let id = self.slots.len();
+        let frame = shape.get_frame_rect();
+        self.tree.insert(id, &frame);
+        self.slots.push(Some(Slot { shape, frame }));
+        id
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Box<dyn Shape>> {
+        let slot = self.slots.get_mut(id)?.take()?;
+        self.tree.remove(id, &slot.frame);
+        Some(slot.shape)
+    }
+
+    pub fn get(&self, id: usize) -> Option<&dyn Shape> {
+        self.slots.get(id)?.as_ref().map(|slot| slot.shape.as_ref())
+    }
+
+    /// # Изменить фигуру и обновить её место в индексе
+    ///
+    /// Возвращает `false`, если фигуры с таким номером нет
+    pub fn update<F: FnOnce(&mut dyn Shape)>(&mut self, id: usize, change: F) -> bool {
+        let Some(Some(slot)) = self.slots.get_mut(id) else {
+            return false;
+        };
+        change(slot.shape.as_mut());
+
+        let frame = slot.shape.get_frame_rect();
+        if frame != slot.frame {
+            self.tree.remove(id, &slot.frame);
+            self.tree.insert(id, &frame);
+            slot.frame = frame;
+        }
+        true
+    }
+
+    pub fn move_by(&mut self, id: usize, x: Float, y: Float) -> bool {
+        self.update(id, |shape| shape.move_by(x, y))
+    }
+
+    pub fn move_at(&mut self, id: usize, target: &Point) -> bool {
+        self.update(id, |shape| shape.move_at(target))
+    }
+
+    pub fn scale(&mut self, id: usize, factor: Float) -> bool {
+        self.update(id, |shape| shape.scale(factor))
+    }
+
+    /// # Номера фигур, чьи ограничивающие прямоугольники пересекают `rect`
+    ///
+    /// Номера возвращаются по возрастанию
+    pub fn query(&self, rect: &Rect) -> Vec<usize> {
+        let mut found = self.tree.query(rect);
+        found.sort_unstable();
+        found
+    }
+
+    /// # Фигура, ограничивающий прямоугольник которой ближе всего к точке
+    pub fn nearest(&self, point: &Point) -> Option<usize> {
+        self.tree.nearest(point)
+    }
+}
+
+impl Default for ShapeIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rectangle::Rectangle;
+    use crate::ellipse::Ellipse;
+
+    /// Детерминированный генератор псевдослучайных прямоугольников
+    fn rects(count: usize) -> Vec<Rect> {
+        let mut state: u32 = 12345;
+        let mut next = move || {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 8) as Float / (1u32 << 24) as Float
+        };
+        (0..count)
+            .map(|_| Rect {
+                pos: Point { x: next() * 100.0, y: next() * 100.0 },
+                width: next() * 5.0,
+                height: next() * 5.0,
+            })
+            .collect()
+    }
+
+    fn brute_query(rects: &[Option<Rect>], rect: &Rect) -> Vec<usize> {
+        let bounds = Bounds::from_rect(rect);
+        (0..rects.len())
+            .filter(|&i| rects[i].as_ref().is_some_and(|r| Bounds::from_rect(r).intersects(&bounds)))
+            .collect()
+    }
+
+    #[test]
+    fn query_matches_brute_force() {
+        let mut all: Vec<Option<Rect>> = rects(500).into_iter().map(Some).collect();
+        let mut tree = RTree::new();
+        for (id, rect) in all.iter().enumerate() {
+            tree.insert(id, rect.as_ref().unwrap());
+        }
+        for id in (0..all.len()).step_by(3) {
+            assert!(tree.remove(id, all[id].as_ref().unwrap()));
+            all[id] = None;
+        }
+
+        assert_eq!(500 - 167, tree.len());
+        for window in rects(20) {
+            let window = Rect { width: window.width * 4.0, height: window.height * 4.0, ..window };
+            let mut found = tree.query(&window);
+            found.sort_unstable();
+            assert_eq!(brute_query(&all, &window), found);
+        }
+    }
+
+    #[test]
+    fn remove_everything() {
+        let all = rects(100);
+        let mut tree = RTree::new();
+        for (id, rect) in all.iter().enumerate() {
+            tree.insert(id, rect);
+        }
+        for (id, rect) in all.iter().enumerate() {
+            assert!(tree.remove(id, rect));
+        }
+
+        assert!(tree.is_empty());
+        assert!(!tree.remove(0, &all[0]));
+        assert_eq!(None, tree.nearest(&Point { x: 0.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let all = rects(300);
+        let mut tree = RTree::new();
+        for (id, rect) in all.iter().enumerate() {
+            tree.insert(id, rect);
+        }
+
+        for probe in rects(20) {
+            let point = probe.pos;
+            let expected = (0..all.len())
+                .min_by(|&a, &b| {
+                    Bounds::from_rect(&all[a]).distance_squared(&point)
+                        .total_cmp(&Bounds::from_rect(&all[b]).distance_squared(&point))
+                })
+                .unwrap();
+            let found = tree.nearest(&point).unwrap();
+            assert_eq!(
+                Bounds::from_rect(&all[expected]).distance_squared(&point),
+                Bounds::from_rect(&all[found]).distance_squared(&point),
+            );
+        }
+    }
+
+    #[test]
+    fn shape_index_follows_moves() {
+        let mut index = ShapeIndex::new();
+        let rectangle = index.insert(Box::new("0.0 0.0 1.0 1.0".parse::<Rectangle>().unwrap()));
+        let ellipse = index.insert(Box::new("10.0 10.0 1.0 1.0".parse::<Ellipse>().unwrap()));
+        let near_origin = Rect { width: 2.0, height: 2.0, pos: Point { x: 0.0, y: 0.0 } };
+
+        assert_eq!(vec![rectangle], index.query(&near_origin));
+
+        index.move_by(rectangle, 20.0, 0.0);
+        index.move_at(ellipse, &Point { x: 0.0, y: 0.0 });
+        assert_eq!(vec![ellipse], index.query(&near_origin));
+        assert_eq!(Some(rectangle), index.nearest(&Point { x: 25.0, y: 0.0 }));
+
+        index.scale(rectangle, 50.0);
+        assert_eq!(vec![rectangle, ellipse], index.query(&near_origin));
+
+        assert!(index.remove(ellipse).is_some());
+        assert!(index.get(ellipse).is_none());
+        assert!(!index.scale(ellipse, 2.0));
+        assert_eq!(vec![rectangle], index.query(&near_origin));
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+use std::fmt::Display;
+
+use crate::base_types::{Point, Rect, Float};
+use crate::outline::Outline;
+use crate::shape::Shape;
+
+const MARGIN: Float = 1.0;
+/// Толщина линий относительно размера рисунка
+const STROKE: Float = 0.005;
+const COLORS: [&str; 4] = ["#1f77b4", "#d62728", "#2ca02c", "#9467bd"];
+
+struct Layer {
+    name: String,
+    outlines: Vec<Outline>,
+    frames: Vec<Rect>,
+}
+
+///
+/// # SVG-документ с фигурами
+///
+/// Фигуры добавляются слоями: каждый слой -- снимок набора фигур
+/// в некоторый момент (например, до и после SCALE). Документ
+/// получается через `Display`
+///
+pub struct Svg {
+    layers: Vec<Layer>,
+    show_frames: bool,
+}
+
+impl Svg {
+    pub fn new() -> Svg {
+        Svg {
+            layers: Vec::new(),
+            show_frames: false,
+        }
+    }
+
+    /// # Рисовать ограничивающие прямоугольники поверх фигур
+    pub fn with_frames(mut self, show_frames: bool) -> Svg {
+        self.show_frames = show_frames;
+        self
+    }
+
+    /// # Добавить слой -- снимок текущего состояния фигур
+    pub fn add_layer(&mut self, name: &str, shapes: &[Box<dyn Shape>]) {
+        self.layers.push(Layer {
+            name: name.to_string(),
+            outlines: shapes.iter().map(|shape| shape.get_outline()).collect(),
+            frames: shapes.iter().map(|shape| shape.get_frame_rect()).collect(),
+        });
+    }
+
+    /// Область рисования: объединение всех ограничивающих прямоугольников
+    fn view_box(&self) -> (Point, Point) {
+        let corners: Vec<Point> = self.layers
+            .iter()
+            .flat_map(|layer| layer.frames.iter())
+            .flat_map(|frame| [
+                Point { x: frame.pos.x - frame.width / 2.0, y: frame.pos.y - frame.height / 2.0 },
+                Point { x: frame.pos.x + frame.width / 2.0, y: frame.pos.y + frame.height / 2.0 },
+            ])
+            .collect();
+
+        if corners.is_empty() {
+            return (
+                Point { x: -MARGIN, y: -MARGIN },
+                Point { x: MARGIN, y: MARGIN },
+            );
+        }
+
+        let bounds = Rect::bounding(&corners);
+        (
+            Point {
+                x: bounds.pos.x - bounds.width / 2.0 - MARGIN,
+                y: bounds.pos.y - bounds.height / 2.0 - MARGIN,
+            },
+            Point {
+                x: bounds.pos.x + bounds.width / 2.0 + MARGIN,
+                y: bounds.pos.y + bounds.height / 2.0 + MARGIN,
+            },
+        )
+    }
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn write_outline(f: &mut fmt::Formatter<'_>, outline: &Outline) -> fmt::Result {
+    match outline {
+        Outline::Rectangle { center, width, height, angle } => {
+            writeln!(
+                f,
+                r#"    <rect x="{}" y="{}" width="{}" height="{}" transform="rotate({} {} {})"/>"#,
+                center.x - width / 2.0, center.y - height / 2.0, width, height,
+                angle, center.x, center.y,
+            )
+        },
+        Outline::Polygon(vertices) => {
+            let points: Vec<String> = vertices
+                .iter()
+                .map(|vertex| format!("{},{}", vertex.x, vertex.y))
+                .collect();
+            writeln!(f, r#"    <polygon points="{}"/>"#, points.join(" "))
+        },
+        Outline::Ellipse { center, hradius, vradius, angle } => {
+            writeln!(
+                f,
+                r#"    <ellipse cx="{}" cy="{}" rx="{}" ry="{}" transform="rotate({} {} {})"/>"#,
+                center.x, center.y, hradius, vradius,
+                angle, center.x, center.y,
+            )
+        },
+        Outline::Group(parts) => {
+            for part in parts {
+                write_outline(f, part)?;
+            }
+            Ok(())
+        },
+    }
+}
+
+impl Display for Svg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min, max) = self.view_box();
+        let width = max.x - min.x;
+        let height = max.y - min.y;
+
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            min.x, -max.y, width, height,
+        )?;
+        // Ось y в SVG направлена вниз, поэтому отражаем всю сцену
+        writeln!(
+            f,
+            r#"<g transform="scale(1 -1)" fill-opacity="0.3" stroke-width="{}">"#,
+            width.max(height) * STROKE,
+        )?;
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let color = COLORS[i % COLORS.len()];
+            writeln!(f, r#"  <g id="{}" fill="{color}" stroke="{color}">"#, layer.name)?;
+            for outline in &layer.outlines {
+                write_outline(f, outline)?;
+            }
+            if self.show_frames {
+                writeln!(
+                    f,
+                    r#"    <g class="frames" fill="none" stroke-dasharray="{} {}">"#,
+                    width.max(height) * STROKE * 4.0,
+                    width.max(height) * STROKE * 2.0,
+                )?;
+                for frame in &layer.frames {
+                    write_outline(f, &Outline::Rectangle {
+                        center: frame.pos,
+                        width: frame.width,
+                        height: frame.height,
+                        angle: 0.0,
+                    })?;
+                }
+                writeln!(f, "    </g>")?;
+            }
+            writeln!(f, "  </g>")?;
+        }
+
+        writeln!(f, "</g>")?;
+        writeln!(f, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rectangle::Rectangle;
+    use crate::triangle::Triangle;
+    use crate::ellipse::Ellipse;
+
+    fn scene() -> Vec<Box<dyn Shape>> {
+        vec![
+            Box::new("0.0 0.0 2.0 1.0".parse::<Rectangle>().unwrap()),
+            Box::new("0.0 0.0 1.0 0.0 0.0 1.0".parse::<Triangle>().unwrap()),
+            Box::new("5.0 5.0 1.0 2.0".parse::<Ellipse>().unwrap()),
+        ]
+    }
+
+    #[test]
+    fn render_all_shapes() {
+        let mut svg = Svg::new();
+        svg.add_layer("before", &scene());
+
+        let document = svg.to_string();
+
+        assert!(document.starts_with("<svg"));
+        assert!(document.contains(r#"<g id="before""#));
+        assert!(document.contains(r#"<rect x="0" y="0" width="2" height="1""#));
+        assert!(document.contains(r#"<polygon points="0,0 1,0 0,1"/>"#));
+        assert!(document.contains(r#"<ellipse cx="5" cy="5" rx="2" ry="1""#));
+        assert!(!document.contains("frames"));
+    }
+
+    #[test]
+    fn view_box_covers_scene() {
+        let mut svg = Svg::new();
+        svg.add_layer("before", &scene());
+
+        // x: [0, 7] и y: [0, 6] с отступом 1, ось y отражена
+        assert!(svg.to_string().contains(r#"viewBox="-1 -7 9 8""#));
+    }
+
+    #[test]
+    fn render_frames_and_layers() {
+        let mut shapes = scene();
+        let mut svg = Svg::new().with_frames(true);
+        svg.add_layer("before", &shapes);
+        shapes[0].scale(2.0);
+        svg.add_layer("after", &shapes);
+
+        let document = svg.to_string();
+
+        assert!(document.contains(r#"<g id="after""#));
+        assert_eq!(2, document.matches(r#"class="frames""#).count());
+        assert!(document.contains(r#"<rect x="-1" y="-0.5" width="4" height="2""#));
+    }
+}
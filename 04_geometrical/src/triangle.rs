@@ -1,11 +1,17 @@
 
 use std::str::FromStr;
 
-use crate::errors::ParseTriangleError;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Diagnostic;
+use crate::parse::Params;
 use crate::shape::Shape;
-use crate::base_types::{Point, Rect, ORIGIN};
+use crate::base_types::{Point, Rect, Float, ORIGIN, collinear, cross};
+use crate::outline::Outline;
+use crate::affine::Affine2;
+use crate::validate::{Validate, ValidationError, check_numbers};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Triangle {
     pub a: Point,
     pub b: Point,
@@ -19,30 +25,40 @@ impl Triangle {
     }
 
     fn center(&self) -> Point {
-        let mut center: Point = self.a.clone() + self.b.clone() + self.c.clone();
+        let mut center: Point = self.a + self.b + self.c;
         center.x /= 3.0;
         center.y /= 3.0;
-        center.clone()
+        center
     }
 
-    fn scale_points(&mut self, factor: f32) {
+    fn scale_points(&mut self, factor: Float) {
         self.a *= factor;
         self.b *= factor;
         self.c *= factor;
     }
-     fn is_valid(&self) -> bool {
-         if self.a == self.b || self.b == self.c || self.a == self.c {
-             return false;
-         }
-         let ab = Point { x: self.b.x - self.a.x, y: self.b.y - self.a.y };
-         let ac = Point { x: self.c.x - self.a.x, y: self.c.y - self.a.y };
-         let cross_product = ab.x * ac.y - ab.y * ac.x;
-         const EPS: f32 = 1e-6;
-         if cross_product.abs() < EPS {
-             return false;
-         }
-         true
-     }
+
+    fn scale_points_xy(&mut self, sx: Float, sy: Float) {
+        for point in [&mut self.a, &mut self.b, &mut self.c] {
+            point.x *= sx;
+            point.y *= sy;
+        }
+    }
+    /// # Каноническая запись треугольника
+    ///
+    /// Вершины против часовой стрелки, начиная с самой левой нижней.
+    /// Треугольники из одних и тех же вершин дают одну и ту же запись
+    pub fn normalized(&self) -> Triangle {
+        let mut points = [self.a, self.b, self.c];
+        if cross(&points[0], &points[1], &points[2]) < 0.0 {
+            points.swap(1, 2);
+        }
+        let first = (0..3)
+            .min_by(|&i, &j| points[i].x.total_cmp(&points[j].x).then(points[i].y.total_cmp(&points[j].y)))
+            .unwrap_or(0);
+        points.rotate_left(first);
+        let [a, b, c] = points;
+        Triangle { a, b, c }
+    }
 }
 
 impl Shape for Triangle {
@@ -51,20 +67,19 @@ impl Shape for Triangle {
         self.center()
     }
 
-    fn get_area(&self) -> f32 {
-        let ab = self.b.clone() - self.a.clone();
-        let ac = self.c.clone() - self.a.clone();
+    fn get_area(&self) -> Float {
+        let ab = self.b - self.a;
+        let ac = self.c - self.a;
 
-        let vec_mul: f32 = (ab.x * ac.y - ab.y * ac.x).abs();
-        let area = 0.5 * vec_mul;
-        area
+        let vec_mul: Float = (ab.x * ac.y - ab.y * ac.x).abs();
+        0.5 * vec_mul
     }
     fn get_frame_rect(&self) -> Rect {
         let x_coords = vec![self.a.x, self.b.x, self.c.x];
         let y_coords = vec![self.a.y, self.b.y, self.c.y];
 
-        let mut x = f32::MAX;
-        let mut x_max = f32::MIN;
+        let mut x = Float::MAX;
+        let mut x_max = Float::MIN;
         for val in x_coords.into_iter() {
             if x > val {
                 x = val;
@@ -74,8 +89,8 @@ impl Shape for Triangle {
             }
         }
 
-        let mut y = f32::MAX;
-        let mut y_max = f32::MIN;
+        let mut y = Float::MAX;
+        let mut y_max = Float::MIN;
         for val in y_coords.into_iter() {
             if y > val {
                 y = val;
@@ -85,8 +100,6 @@ impl Shape for Triangle {
             }
         }
 
-        let left_bot = Point { x, y };
-
         let width = x_max - x;
         let height = y_max - y;
     
@@ -106,7 +119,7 @@ impl Shape for Triangle {
         self.move_by(delta.x, delta.y);
     }
 
-    fn move_by(&mut self, x: f32, y: f32) {
+    fn move_by(&mut self, x: Float, y: Float) {
         self.a.x += x;
         self.a.y += y;
 
@@ -117,7 +130,7 @@ impl Shape for Triangle {
         self.c.y += y;
 
     }
-    fn scale(&mut self, factor: f32) {
+    fn scale(&mut self, factor: Float) {
         let center = self.center();
 
         self.move_at(&ORIGIN);
@@ -126,93 +139,72 @@ impl Shape for Triangle {
 
         self.move_at(&center);
     }
+
+    fn scale_xy(&mut self, sx: Float, sy: Float) {
+        let center = self.center();
+
+        self.move_at(&ORIGIN);
+
+        self.scale_points_xy(sx, sy);
+
+        self.move_at(&center);
+    }
+
+    fn get_outline(&self) -> Outline {
+        Outline::Polygon(vec![self.a, self.b, self.c])
+    }
+
+    fn rotate(&mut self, angle: Float) {
+        let center = self.center();
+
+        self.a = self.a.rotated(&center, angle);
+        self.b = self.b.rotated(&center, angle);
+        self.c = self.c.rotated(&center, angle);
+    }
+
+    fn transform(&mut self, affine: &Affine2) {
+        self.a = affine.apply(&self.a);
+        self.b = affine.apply(&self.b);
+        self.c = affine.apply(&self.c);
+    }
 }
 
 
+impl Validate for Triangle {
+    /// # Проверка корректности треугольника
+    ///
+    /// Вершины различны и не лежат на одной прямой
+    fn validate(&self) -> Result<(), ValidationError> {
+        check_numbers(&[self.a, self.b, self.c], &[])?;
+        if self.a == self.b || self.b == self.c || self.a == self.c || collinear(&self.a, &self.b, &self.c) {
+            return Err(ValidationError::ZeroArea);
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for Triangle {
-    type Err = ParseTriangleError;
+    type Err = Diagnostic;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> { 
+        let mut params = Params::new("TRIANGLE", s, 6);
 
-        let mut tokens = s
-            .split_whitespace();
-
-        let x1 = tokens.next().ok_or(
-            ParseTriangleError {
-                place: s.to_string(),
-                cause: "No coords provided (need 6)".to_string(),
-            })?;
-        let y1 = tokens.next().ok_or(
-            ParseTriangleError {
-                place: s.to_string(),
-                cause: "Not enough coords (need 6)".to_string(),
-            })?;
-        let x2 = tokens.next().ok_or(
-            ParseTriangleError {
-                place: s.to_string(),
-                cause: "Not enough coords (need 6)".to_string(),
-            })?;
-        let y2 = tokens.next().ok_or(
-            ParseTriangleError {
-                place: s.to_string(),
-                cause: "Not enough coords (need 6)".to_string(),
-            })?;
-        let x3 = tokens.next().ok_or(
-            ParseTriangleError {
-                place: s.to_string(),
-                cause: "Not enough coords (need 6)".to_string(),
-            })?;
-        let y3 = tokens.next().ok_or(
-            ParseTriangleError {
-                place: s.to_string(),
-                cause: "Not enough coords (need 6)".to_string(),
-            })?;
-        let x = x1.parse::<f32>().map_err(
-            |_| ParseTriangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", x1).to_string(),
-            })?;
-        let y = y1.parse::<f32>().map_err(
-            |_| ParseTriangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", y1).to_string(),
-            })?;
+        let x = params.next_float()?;
+        let y = params.next_float()?;
         let a = Point { x, y };
 
-
-        let x = x2.parse::<f32>().map_err(
-            |_| ParseTriangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", x2).to_string(),
-            })?;
-        let y = y2.parse::<f32>().map_err(
-            |_| ParseTriangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", y2).to_string(),
-            })?;
+        let x = params.next_float()?;
+        let y = params.next_float()?;
         let b = Point { x, y };
 
-        let x = x3.parse::<f32>().map_err(
-            |_| ParseTriangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", x3).to_string(),
-            })?;
-        let y = y3.parse::<f32>().map_err(
-            |_| ParseTriangleError {
-                place: s.to_string(),
-                cause: format!("Cannot parse `{}', expected float32", y3).to_string(),
-            })?;
+        let x = params.next_float()?;
+        let y = params.next_float()?;
         let c = Point { x, y };
         
         let tri = Triangle::new(a, b, c);
-        if !tri.is_valid() {
-            return Err(ParseTriangleError {
-                place: s.to_string(),
-                cause: "Invalid triangle".to_string(),
-            })
-        }
+        tri.validate().map_err(|err| params.invalid(err))?;
 
-        Ok(Triangle::new(a, b, c))
+        Ok(tri)
     }
 }
 
@@ -220,6 +212,7 @@ impl FromStr for Triangle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::ErrorKind;
 
     #[test]
     fn parse_correct_triangle() {
@@ -236,6 +229,28 @@ mod tests {
     }
 
 
+    #[test]
+    fn parse_invalid_number_reports_token() {
+        let err = "2 1 a 3 1 2".parse::<Triangle>().unwrap_err();
+
+        assert_eq!(ErrorKind::InvalidNumber, err.kind);
+        assert_eq!(Some(2), err.token);
+        assert_eq!(5, err.column);
+    }
+
+    #[test]
+    fn parse_collinear_is_invalid_geometry() {
+        let err = "0 0 1 1 2 2".parse::<Triangle>().unwrap_err();
+
+        assert_eq!(ErrorKind::InvalidGeometry, err.kind);
+    }
+
+    #[test]
+    fn degeneracy_does_not_depend_on_scale() {
+        assert!("0 0 0.0001 0 0 0.0001".parse::<Triangle>().is_ok());
+        assert!("0 0 1000 0 2000 0.0001".parse::<Triangle>().is_err());
+    }
+
     #[test]
     fn center_correct() {
         let triangle = Triangle::new(
@@ -325,4 +340,65 @@ mod tests {
         let _ = relative_eq!(expected.c.y, triangle.c.y);
     }
 
+    #[test]
+    fn rotate_about_correct() {
+        let mut triangle = Triangle::new(
+            Point { x:1.0, y:0.0 },
+            Point { x:2.0, y:0.0 },
+            Point { x:1.0, y:1.0 },
+        );
+
+        triangle.rotate_about(&ORIGIN, 90.0);
+
+        assert_relative_eq!(0.0, triangle.a.x, epsilon = 1e-6);
+        assert_relative_eq!(1.0, triangle.a.y, epsilon = 1e-6);
+        assert_relative_eq!(0.0, triangle.b.x, epsilon = 1e-6);
+        assert_relative_eq!(2.0, triangle.b.y, epsilon = 1e-6);
+        assert_relative_eq!(-1.0, triangle.c.x, epsilon = 1e-6);
+        assert_relative_eq!(1.0, triangle.c.y, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn scale_xy_keeps_center() {
+        let mut triangle = Triangle::new(
+            Point { x:0.0, y:0.0 },
+            Point { x:3.0, y:0.0 },
+            Point { x:0.0, y:3.0 },
+        );
+
+        triangle.scale_xy(2.0, 1.0);
+
+        assert_eq!(Point { x:1.0, y:1.0 }, triangle.get_center());
+        assert_eq!(Point { x:-1.0, y:0.0 }, triangle.a);
+        assert_eq!(9.0, triangle.get_area());
+    }
+
+    #[test]
+    fn transform_shear() {
+        let mut triangle = Triangle::new(
+            Point { x:0.0, y:0.0 },
+            Point { x:2.0, y:0.0 },
+            Point { x:0.0, y:2.0 },
+        );
+
+        triangle.transform(&Affine2::shear(1.0, 0.0));
+
+        assert_eq!(Point { x:2.0, y:2.0 }, triangle.c);
+        assert_eq!(2.0, triangle.get_area());
+    }
+
+    #[test]
+    fn normalized_ignores_vertex_order() {
+        let triangle = Triangle::new(
+            Point { x:2.0, y:0.0 },
+            Point { x:0.0, y:2.0 },
+            Point { x:0.0, y:0.0 },
+        );
+        let reversed = Triangle::new(triangle.c, triangle.b, triangle.a);
+
+        assert_eq!(triangle.normalized(), reversed.normalized());
+        assert_eq!(Point { x:0.0, y:0.0 }, triangle.normalized().a);
+        assert_eq!(Point { x:2.0, y:0.0 }, triangle.normalized().b);
+        assert!(triangle.approx_eq(&reversed, 1e-4));
+    }
 }
@@ -0,0 +1,209 @@
+use crate::base_types::{Float, Point};
+use crate::shape::Shape;
+
+/// Допуск приближения эллипсов многоугольниками по умолчанию
+pub const DEFAULT_TOLERANCE: Float = 1e-3;
+
+/// Расстояние до прямой, на котором точка считается лежащей на ней
+const COLLINEAR: f64 = 1e-9;
+
+/// Вершина в `f64`: объединение считается с двойной точностью
+/// независимо от типа координат
+type Vertex = (f64, f64);
+
+fn cross(o: Vertex, a: Vertex, b: Vertex) -> f64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+/// С какой стороны от прямой `ab` лежит `c`: 1 -- слева, -1 -- справа, 0 -- на прямой
+fn side(a: Vertex, b: Vertex, c: Vertex) -> i32 {
+    let distance = cross(a, b, c) / (b.0 - a.0).hypot(b.1 - a.1);
+    if distance > COLLINEAR {
+        1
+    } else if distance < -COLLINEAR {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Положение проекции `c` на отрезок `ab`: 0 в `a`, 1 в `b`
+fn position(a: Vertex, b: Vertex, c: Vertex) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    ((c.0 - a.0) * dx + (c.1 - a.1) * dy) / (dx * dx + dy * dy)
+}
+
+fn signed_area(polygon: &[Vertex]) -> f64 {
+    let n = polygon.len();
+    (0..n)
+        .map(|i| {
+            let (p, q) = (polygon[i], polygon[(i + 1) % n]);
+            p.0 * q.1 - q.0 * p.1
+        })
+        .sum::<f64>() / 2.0
+}
+
+/// Доля стороны `ab` многоугольника `index`, не покрытая остальными
+///
+/// Каждое пересечение со стороной другого многоугольника открывает
+/// или закрывает покрытый участок. Из совпадающих сонаправленных
+/// сторон учитывается только сторона многоугольника с меньшим номером
+fn uncovered_fraction(polygons: &[Vec<Vertex>], index: usize, a: Vertex, b: Vertex) -> f64 {
+    let mut events: Vec<(f64, i32)> = vec![(0.0, 0), (1.0, 0)];
+    for (other, polygon) in polygons.iter().enumerate() {
+        if other == index {
+            continue;
+        }
+        let n = polygon.len();
+        for k in 0..n {
+            let (c, d) = (polygon[k], polygon[(k + 1) % n]);
+            let (sc, sd) = (side(a, b, c), side(a, b, d));
+            if sc != sd {
+                if sc.min(sd) < 0 {
+                    let sa = cross(c, d, a);
+                    let sb = cross(c, d, b);
+                    events.push((sa / (sa - sb), (sc - sd).signum()));
+                }
+            } else if sc == 0 && other < index {
+                let same_direction = (b.0 - a.0) * (d.0 - c.0) + (b.1 - a.1) * (d.1 - c.1) > 0.0;
+                if same_direction {
+                    events.push((position(a, b, c), 1));
+                    events.push((position(a, b, d), -1));
+                }
+            }
+        }
+    }
+
+    events.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.cmp(&y.1)));
+    let mut uncovered = 0.0;
+    let mut coverage = events[0].1;
+    for pair in events.windows(2) {
+        if coverage == 0 {
+            uncovered += pair[1].0.clamp(0.0, 1.0) - pair[0].0.clamp(0.0, 1.0);
+        }
+        coverage += pair[1].1;
+    }
+    uncovered
+}
+
+///
+/// # Площадь объединения многоугольников
+///
+/// По формуле площади Гаусса по границе объединения: каждая сторона
+/// учитывается только в той части, что не лежит внутри других
+/// многоугольников. Многоугольники должны быть простыми, порядок
+/// обхода вершин любой
+///
+// С feature `f64` приведения типов становятся пустыми
+#[allow(clippy::unnecessary_cast)]
+pub fn polygons_union_area(polygons: &[Vec<Point>]) -> Float {
+    let polygons: Vec<Vec<Vertex>> = polygons
+        .iter()
+        .map(|polygon| polygon.iter().map(|p| (p.x as f64, p.y as f64)).collect::<Vec<_>>())
+        .filter(|polygon: &Vec<Vertex>| polygon.len() >= 3 && signed_area(polygon) != 0.0)
+        .map(|mut polygon| {
+            // Алгоритм рассчитан на обход против часовой стрелки
+            if signed_area(&polygon) < 0.0 {
+                polygon.reverse();
+            }
+            polygon
+        })
+        .collect();
+
+    let mut area = 0.0;
+    for (index, polygon) in polygons.iter().enumerate() {
+        let n = polygon.len();
+        for k in 0..n {
+            let (a, b) = (polygon[k], polygon[(k + 1) % n]);
+            if a == b {
+                continue;
+            }
+            area += (a.0 * b.1 - b.0 * a.1) * uncovered_fraction(&polygons, index, a, b);
+        }
+    }
+    (area / 2.0) as Float
+}
+
+///
+/// # Площадь, покрытая фигурами
+///
+/// В отличие от суммы `get_area()` перекрытия учитываются один раз.
+/// Многоугольники и прямоугольники считаются точно, эллипсы
+/// заменяются многоугольниками с отклонением не больше `tolerance`
+///
+pub fn union_area(shapes: &[Box<dyn Shape>], tolerance: Float) -> Float {
+    let polygons: Vec<Vec<Point>> = shapes
+        .iter()
+        .flat_map(|shape| shape.get_outline().polygons(tolerance))
+        .collect();
+    polygons_union_area(&polygons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rectangle::Rectangle;
+    use crate::triangle::Triangle;
+    use crate::ellipse::Ellipse;
+    use crate::polygon::Polygon;
+    use crate::base_types::consts::PI;
+
+    fn rectangle(s: &str) -> Box<dyn Shape> {
+        Box::new(s.parse::<Rectangle>().unwrap())
+    }
+
+    #[test]
+    fn overlapping_rectangles() {
+        let shapes = vec![rectangle("0 0 1 1"), rectangle("0.5 0.5 1.5 1.5")];
+
+        assert_relative_eq!(1.75, union_area(&shapes, DEFAULT_TOLERANCE), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn coincident_and_touching() {
+        let same = vec![rectangle("0 0 1 1"), rectangle("0 0 1 1")];
+        let touching = vec![rectangle("0 0 1 1"), rectangle("1 0 2 1")];
+        let shared_edge = vec![rectangle("0 0 2 1"), rectangle("0 0 1 1")];
+
+        assert_relative_eq!(1.0, union_area(&same, DEFAULT_TOLERANCE), epsilon = 1e-6);
+        assert_relative_eq!(2.0, union_area(&touching, DEFAULT_TOLERANCE), epsilon = 1e-6);
+        assert_relative_eq!(2.0, union_area(&shared_edge, DEFAULT_TOLERANCE), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn nested_and_disjoint() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            rectangle("0 0 4 4"),
+            Box::new("1 1 2 1 1 2".parse::<Triangle>().unwrap()),
+            rectangle("10 10 11 12"),
+        ];
+
+        assert_relative_eq!(18.0, union_area(&shapes, DEFAULT_TOLERANCE), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn non_convex_polygon() {
+        // Буква L площадью 3 и квадрат, закрывающий её выемку и часть ножки
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new("0 0 2 0 2 1 1 1 1 2 0 2".parse::<Polygon>().unwrap()),
+            rectangle("1 0.5 2 2"),
+        ];
+
+        assert_relative_eq!(3.0 + 1.0, union_area(&shapes, DEFAULT_TOLERANCE), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn ellipse_within_tolerance() {
+        let circle: Vec<Box<dyn Shape>> = vec![Box::new("0 0 1 1".parse::<Ellipse>().unwrap())];
+        let halves: Vec<Box<dyn Shape>> = vec![
+            Box::new("0 0 1 1".parse::<Ellipse>().unwrap()),
+            rectangle("0 -1 1 1"),
+        ];
+
+        // Вписанный многоугольник теряет не больше периметра на допуск
+        let area = union_area(&circle, 1e-4);
+        assert!(area <= PI && PI - area < 2.0 * PI * 1e-4);
+        let area = union_area(&halves, 1e-4);
+        assert!((PI / 2.0 + 2.0 - area).abs() < PI * 1e-4);
+    }
+}
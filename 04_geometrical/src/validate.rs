@@ -0,0 +1,106 @@
+use std::error;
+use std::fmt;
+use std::fmt::Display;
+
+use crate::base_types::{Float, Point};
+use crate::shape::Shape;
+
+/// Наибольшая допустимая по модулю координата. С таким запасом
+/// площади и размеры фигур ещё представимы в `Float`
+pub const MAX_COORDINATE: Float = 1e18;
+
+///
+/// # Нарушенное правило корректности фигуры
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Координата, размер или угол -- NaN или бесконечность
+    NotFinite,
+    /// Координата больше `MAX_COORDINATE` по модулю, обычно после
+    /// масштабирования
+    Overflow,
+    /// Правый верхний угол прямоугольника левее или ниже левого нижнего
+    InvertedCorners,
+    /// Полуось эллипса отрицательна
+    NegativeRadius,
+    /// Площадь фигуры равна нулю: совпадающие углы, вершины на одной
+    /// прямой, нулевая полуось
+    ZeroArea,
+    /// У многоугольника меньше трёх вершин
+    TooFewVertices,
+    /// У многоугольника есть совпадающие вершины
+    DuplicateVertices,
+    /// Стороны многоугольника пересекаются
+    SelfIntersection,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cause = match self {
+            ValidationError::NotFinite => "coordinates must be finite numbers",
+            ValidationError::Overflow => "coordinates are too large",
+            ValidationError::InvertedCorners => "right top point coords must be greater than left bot point",
+            ValidationError::NegativeRadius => "radii must not be negative",
+            ValidationError::ZeroArea => "shape has zero area",
+            ValidationError::TooFewVertices => "polygon needs at least 3 vertices",
+            ValidationError::DuplicateVertices => "polygon vertices must be different",
+            ValidationError::SelfIntersection => "polygon sides must not intersect",
+        };
+        write!(f, "{}", cause)
+    }
+}
+
+impl error::Error for ValidationError {}
+
+///
+/// # Проверка корректности фигуры
+///
+/// Одни и те же правила применяются при разборе фигуры из текста
+/// и JSON и после каждого преобразования в `History`
+///
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+/// # Все числа конечны и не больше `MAX_COORDINATE` по модулю
+///
+/// Общее правило для всех фигур: `values` -- углы и другие величины,
+/// которые проверяются только на конечность
+pub fn check_numbers(points: &[Point], values: &[Float]) -> Result<(), ValidationError> {
+    let coordinates = points.iter().flat_map(|point| [point.x, point.y]);
+    if !coordinates.clone().chain(values.iter().copied()).all(Float::is_finite) {
+        return Err(ValidationError::NotFinite);
+    }
+    if coordinates.into_iter().any(|value| value.abs() > MAX_COORDINATE) {
+        return Err(ValidationError::Overflow);
+    }
+    Ok(())
+}
+
+/// # Проверить фигуры с номерами `targets`
+///
+/// Возвращает номер первой некорректной фигуры и нарушенное правило.
+/// Несуществующие номера пропускаются
+pub fn validate_targets(shapes: &[Box<dyn Shape>], targets: &[usize]) -> Result<(), (usize, ValidationError)> {
+    for &i in targets {
+        if let Some(shape) = shapes.get(i) {
+            shape.validate().map_err(|error| (i, error))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_must_be_finite_and_bounded() {
+        let point = |x, y| Point { x, y };
+
+        assert_eq!(Ok(()), check_numbers(&[point(1.0, -1e17)], &[45.0]));
+        assert_eq!(Err(ValidationError::NotFinite), check_numbers(&[point(Float::NAN, 0.0)], &[]));
+        assert_eq!(Err(ValidationError::NotFinite), check_numbers(&[point(0.0, 0.0)], &[Float::INFINITY]));
+        assert_eq!(Err(ValidationError::Overflow), check_numbers(&[point(0.0, -2e18)], &[]));
+    }
+}
@@ -0,0 +1,63 @@
+//! Сравнение вывода программы на `shapes.txt` с эталонными файлами
+//!
+//! Эталоны лежат в `tests/golden`. После намеренного изменения формата
+//! вывода их нужно пересоздать, например:
+//! `cargo run -- < shapes.txt > tests/golden/shapes.out`
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+fn run(args: &[&str]) -> String {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let input = fs::read(root.join("shapes.txt")).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_geometrical"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn golden(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name);
+    fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn default_format() {
+    assert_eq!(golden("shapes.out"), run(&[]));
+}
+
+#[test]
+fn half_even_two_digits() {
+    assert_eq!(
+        golden("shapes-half-even.out"),
+        run(&["--precision", "2", "--rounding", "half-even"]),
+    );
+}
+
+#[test]
+fn failed_scale_reports_its_error() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_geometrical"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"RECTANGLE 0 0 1 1\nSCALE 0 0 abc\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("Cannot parse `abc'"), "{stderr}");
+    assert!(!stderr.contains("SCALE command expected!"), "{stderr}");
+}
@@ -0,0 +1,131 @@
+//! Свойства фигур на случайных данных
+//!
+//! Проверяются инварианты, которые должны выполняться для любого
+//! прямоугольника, треугольника и эллипса: масштабирование меняет
+//! площадь в квадрат коэффициента, сдвиг сохраняет площадь и размеры,
+//! ограничивающий прямоугольник содержит все вершины, масштабирование
+//! туда и обратно даёт равную с допуском фигуру
+
+use geometrical::base_types::{Float, Point, Rect};
+use geometrical::ellipse::Ellipse;
+use geometrical::rectangle::Rectangle;
+use geometrical::shape::Shape;
+use geometrical::triangle::Triangle;
+
+use proptest::prelude::*;
+
+/// Допуск сравнения, относительный для больших величин
+const EPSILON: Float = 1e-3;
+
+fn close(expected: Float, actual: Float) -> bool {
+    (expected - actual).abs() <= EPSILON * expected.abs().max(actual.abs()).max(1.0)
+}
+
+fn point() -> impl Strategy<Value = Point> {
+    (-100.0..100.0 as Float, -100.0..100.0 as Float).prop_map(|(x, y)| Point { x, y })
+}
+
+fn size() -> impl Strategy<Value = Float> {
+    0.1..50.0 as Float
+}
+
+fn angle() -> impl Strategy<Value = Float> {
+    -360.0..360.0 as Float
+}
+
+fn rectangle() -> impl Strategy<Value = Rectangle> {
+    (point(), size(), size(), angle()).prop_map(|(left_bot, width, height, angle)| Rectangle {
+        left_bot,
+        right_top: Point { x: left_bot.x + width, y: left_bot.y + height },
+        angle,
+        shear: 0.0,
+    })
+}
+
+fn triangle() -> impl Strategy<Value = Triangle> {
+    (point(), point(), point())
+        .prop_map(|(a, b, c)| Triangle { a, b, c })
+        // Почти вырожденные треугольники теряют площадь на округлении
+        .prop_filter("degenerate triangle", |triangle| triangle.get_area() > 1.0)
+}
+
+fn ellipse() -> impl Strategy<Value = Ellipse> {
+    (point(), size(), size(), angle()).prop_map(|(center, vradius, hradius, angle)| Ellipse {
+        center,
+        vradius,
+        hradius,
+        angle,
+    })
+}
+
+fn shape() -> impl Strategy<Value = Box<dyn Shape>> {
+    prop_oneof![
+        rectangle().prop_map(|shape| Box::new(shape) as Box<dyn Shape>),
+        triangle().prop_map(|shape| Box::new(shape) as Box<dyn Shape>),
+        ellipse().prop_map(|shape| Box::new(shape) as Box<dyn Shape>),
+    ]
+}
+
+/// Точка внутри прямоугольника с допуском на округление
+fn frame_contains(frame: &Rect, point: &Point) -> bool {
+    let tolerance = EPSILON * frame.pos.x.abs().max(frame.pos.y.abs()).max(1.0);
+    (point.x - frame.pos.x).abs() <= frame.width / 2.0 + tolerance
+        && (point.y - frame.pos.y).abs() <= frame.height / 2.0 + tolerance
+}
+
+proptest! {
+    #[test]
+    fn scale_multiplies_area_by_square(mut shape in shape(), factor in 0.1..10.0 as Float) {
+        let area = shape.get_area();
+
+        shape.scale(factor);
+
+        prop_assert!(close(area * factor * factor, shape.get_area()));
+    }
+
+    #[test]
+    fn move_by_keeps_area_and_frame_size(mut shape in shape(), delta in point()) {
+        let area = shape.get_area();
+        let frame = shape.get_frame_rect();
+
+        shape.move_by(delta.x, delta.y);
+        let moved = shape.get_frame_rect();
+
+        prop_assert!(close(area, shape.get_area()));
+        prop_assert!(close(frame.width, moved.width));
+        prop_assert!(close(frame.height, moved.height));
+        prop_assert!(close(frame.pos.x + delta.x, moved.pos.x));
+        prop_assert!(close(frame.pos.y + delta.y, moved.pos.y));
+    }
+
+    #[test]
+    fn move_at_then_center_is_target(mut shape in shape(), target in point()) {
+        shape.move_at(&target);
+        let center = shape.get_center();
+
+        prop_assert!(close(target.x, center.x));
+        prop_assert!(close(target.y, center.y));
+    }
+
+    #[test]
+    fn frame_contains_vertices(mut shape in shape(), angle in angle()) {
+        shape.rotate(angle);
+        let frame = shape.get_frame_rect();
+
+        for polygon in shape.get_outline().polygons(EPSILON) {
+            for vertex in &polygon {
+                prop_assert!(frame_contains(&frame, vertex), "{:?} outside {:?}", vertex, frame);
+            }
+        }
+    }
+
+    #[test]
+    fn scale_there_and_back_is_approx_eq(mut shape in shape(), factor in 0.1..10.0 as Float) {
+        let original = shape.clone();
+
+        shape.scale(factor);
+        shape.scale(1.0 / factor);
+
+        prop_assert!(shape.approx_eq(original.as_ref(), EPSILON));
+    }
+}